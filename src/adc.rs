@@ -0,0 +1,230 @@
+use core::fmt;
+use core::ptr;
+use crate::target_device::{ADC0, ADC1};
+use crate::{Channel, TriggerSource, TransferDescriptor, BeatSize, BlockAction, Interrupts};
+
+/// ADC instance used for DMA sequencing.
+pub enum Adc {
+    /// The first ADC, sequenced by `Adc0Seq` and read on `Adc0ResRdy`.
+    Adc0,
+    /// The second ADC, sequenced by `Adc1Seq` and read on `Adc1ResRdy`.
+    Adc1,
+}
+
+impl Adc {
+    fn sequence_source(&self) -> TriggerSource {
+        match self {
+            Adc::Adc0 => TriggerSource::Adc0Seq,
+            Adc::Adc1 => TriggerSource::Adc1Seq,
+        }
+    }
+
+    fn result_source(&self) -> TriggerSource {
+        match self {
+            Adc::Adc0 => TriggerSource::Adc0ResRdy,
+            Adc::Adc1 => TriggerSource::Adc1ResRdy,
+        }
+    }
+
+    fn dseqdata_addr(&self) -> *const () {
+        match self {
+            Adc::Adc0 => unsafe { &(*ADC0::ptr()).dseqdata as *const _ as *const () },
+            Adc::Adc1 => unsafe { &(*ADC1::ptr()).dseqdata as *const _ as *const () },
+        }
+    }
+
+    fn result_addr(&self) -> *const () {
+        match self {
+            Adc::Adc0 => unsafe { &(*ADC0::ptr()).result as *const _ as *const () },
+            Adc::Adc1 => unsafe { &(*ADC1::ptr()).result as *const _ as *const () },
+        }
+    }
+}
+
+/// Error type for the kinds of errors that can occur while reading frames from an [`AdcScan`].
+///
+/// [`AdcScan`]: struct.AdcScan.html
+#[derive(Debug)]
+pub enum AdcScanError {
+    /// The DMA engine lapped the reader, overwriting frames that had not been read yet. The reader has been moved to
+    /// the newest completed frame, and `lost` frames were discarded.
+    Overrun {
+        /// The amount of frames that were completed but never read.
+        lost: usize,
+    },
+}
+
+impl fmt::Display for AdcScanError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdcScanError::Overrun { lost } => write!(w, "The sample buffer overran, {} frames were lost.", lost),
+        }
+    }
+}
+
+/// Paired-channel ADC scanner using DMA sequencing.
+///
+/// The sequencer channel writes the words of `sequence` to the ADC DSEQDATA register each time the ADC requests new
+/// sequence data, looping over the sequence forever. The reader channel copies the RESULT register into a ring of
+/// frames each time a conversion completes. Each frame is filled by its own block transfer, which raises a transfer
+/// complete interrupt on the reader channel. Interrupts for several frames can be handled at once, so completed
+/// frames are counted from the descriptor the reader has written back, not from the interrupts.
+///
+/// The ADC must be configured separately, with DSEQCTRL selecting the registers updated by each sequence word, and
+/// the frame length should be a multiple of the conversions in one scan of `sequence` to keep frames aligned.
+///
+/// Call [`handle_interrupt`] from the DMAC interrupt vector to account for completed frames, and [`next_frame`] to
+/// consume them.
+///
+/// [`handle_interrupt`]: #method.handle_interrupt
+/// [`next_frame`]: #method.next_frame
+pub struct AdcScan {
+    sequencer: Channel,
+    reader: Channel,
+    samples: &'static mut [u16],
+    descriptors: &'static mut [TransferDescriptor],
+    frame_len: usize,
+    completed: u64,
+    read: u64,
+}
+
+impl AdcScan {
+    /// Create a scanner for `adc`, storing results in `samples`.
+    ///
+    /// The reader channel uses its first descriptor plus every descriptor in `descriptors`, one per frame, so
+    /// `samples` is split into `descriptors.len() + 1` frames of equal length. The channels are configured, but not
+    /// enabled until [`start`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sequence` is empty or longer than 65535 words, or if `samples` can't be split evenly into frames
+    /// of 1 to 65535 samples.
+    ///
+    /// [`start`]: #method.start
+    pub fn new(adc: Adc, mut sequencer: Channel, mut reader: Channel, sequence: &'static [u32],
+        samples: &'static mut [u16], descriptors: &'static mut [TransferDescriptor]) -> AdcScan
+    {
+        let frames = descriptors.len() + 1;
+        let frame_len = samples.len() / frames;
+        assert!(!sequence.is_empty() && sequence.len() <= u16::MAX as usize);
+        assert!(frame_len > 0 && frame_len <= u16::MAX as usize && frame_len * frames == samples.len());
+
+        sequencer.disable();
+        sequencer.set_beat_trigger(adc.sequence_source());
+        let desc = sequencer.get_first_descriptor();
+        let desc_addr = desc as *mut _;
+        *desc = Default::default();
+        desc.set_beat_size(BeatSize::Word);
+        desc.set_block_count(sequence.len() as u16);
+        desc.set_src_addr_increment(true);
+        desc.set_src_start_addr(sequence.as_ptr() as *const ());
+        desc.set_dst_addr(adc.dseqdata_addr());
+        desc.set_next_desc_addr(desc_addr);
+        desc.set_valid();
+
+        reader.disable();
+        reader.set_beat_trigger(adc.result_source());
+        reader.enable_interrupts(Interrupts::TCMPL);
+        let slots = reader.chain_slots(descriptors);
+        for i in 0..frames {
+            let desc = unsafe { &mut *slots.get(i) };
+            *desc = Default::default();
+            desc.set_beat_size(BeatSize::HWord);
+            desc.set_block_count(frame_len as u16);
+            desc.set_dest_addr_increment(true);
+            desc.set_block_action(BlockAction::Int);
+            desc.set_src_addr(adc.result_addr());
+            desc.set_dst_start_addr(samples[i * frame_len..].as_ptr() as *const ());
+            desc.set_next_desc_addr(slots.get((i + 1) % frames));
+            desc.set_valid();
+        }
+
+        AdcScan {
+            sequencer,
+            reader,
+            samples,
+            descriptors,
+            frame_len,
+            completed: 0,
+            read: 0,
+        }
+    }
+
+    /// Start scanning. Any frames left in the sample buffer are discarded.
+    ///
+    /// The reader is enabled before the sequencer, so no result can be missed once the ADC starts converting.
+    pub fn start(&mut self) {
+        self.completed = 0;
+        self.read = 0;
+        unsafe { ptr::write_volatile(self.reader.get_writeback_descriptor(), TransferDescriptor::new()) };
+        self.reader.clear_interrupt_flags();
+        self.reader.enable();
+        self.sequencer.enable();
+    }
+
+    /// Stop scanning. Frames already completed can still be read.
+    pub fn stop(&mut self) {
+        self.sequencer.disable();
+        self.reader.disable();
+    }
+
+    /// Return the number of samples in a frame.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Account for frames completed by the DMA engine.
+    ///
+    /// The frame being filled is found from the next descriptor address the reader has written back, and every frame
+    /// before it since the last call is counted as completed. The transfer complete flag is only used to tell a whole
+    /// lap of the ring apart from no progress at all.
+    ///
+    /// This should be called from the DMAC interrupt vector when the reader channel has a pending interrupt.
+    pub fn handle_interrupt(&mut self) {
+        let frames = self.descriptors.len() + 1;
+        // Read the flag before the write-back, so a set flag always comes with a frame the write-back accounts for.
+        let tcmpl = self.reader.get_interrupt_flags().contains(Interrupts::TCMPL);
+        let next = self.reader.read_writeback_descriptor().get_next_desc_addr();
+        self.reader.clear_interrupt_flags();
+
+        let slots = self.reader.chain_slots(self.descriptors);
+        let active = match next.and_then(|next| slots.index_of(next)) {
+            Some(index) => (index + frames - 1) % frames,
+            None => return,
+        };
+        let delta = (active + frames - (self.completed % frames as u64) as usize) % frames;
+        self.completed += match delta {
+            0 if tcmpl => frames as u64,
+            _ => delta as u64,
+        };
+    }
+
+    /// Return the oldest completed frame that has not been read yet, if there is one.
+    ///
+    /// The frame stays valid until the DMA engine laps back around to it, which happens after every other frame in
+    /// the ring has been filled. If the DMA engine has already lapped the reader, `AdcScanError::Overrun` is returned
+    /// and the unread frames are discarded.
+    pub fn next_frame(&mut self) -> Result<Option<&[u16]>, AdcScanError> {
+        self.handle_interrupt();
+        let frames = self.descriptors.len() as u64 + 1;
+        let pending = self.completed - self.read;
+        // The frame after the newest completed frame is being filled by the DMA engine.
+        if pending >= frames {
+            self.read = self.completed;
+            return Err(AdcScanError::Overrun { lost: pending as usize });
+        }
+        if pending == 0 {
+            return Ok(None);
+        }
+
+        let start = (self.read % frames) as usize * self.frame_len;
+        self.read += 1;
+        Ok(Some(&self.samples[start..start + self.frame_len]))
+    }
+
+    /// Stop the scanner, returning the sequencer and reader channels, the sample buffer and the frame descriptors.
+    pub fn free(mut self) -> (Channel, Channel, &'static mut [u16], &'static mut [TransferDescriptor]) {
+        self.stop();
+        (self.sequencer, self.reader, self.samples, self.descriptors)
+    }
+}
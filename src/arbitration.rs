@@ -0,0 +1,160 @@
+use crate::{Channels, Priority};
+
+const LEVELS: [Priority; 4] = [Priority::Level0, Priority::Level1, Priority::Level2, Priority::Level3];
+
+/// Scheduling of the channels within a priority level.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scheduling {
+    /// The channel with the lowest channel number is always granted access first.
+    Static,
+    /// Access is granted to each pending channel in turn, after the channel granted last.
+    RoundRobin,
+}
+
+/// Arbitration scheme of the DMA system.
+///
+/// The scheme declares which priority levels take part in arbitration, how each level schedules its channels, and
+/// which channels belong to each level. Apply it with [`DMAController::apply_arbitration()`], and read back the
+/// scheme in use with [`DMAController::arbitration()`].
+///
+/// # Example
+///
+/// ```
+/// use samd_dma::{Arbitration, Channels, Priority, Scheduling};
+///
+/// let arbitration = Arbitration::new()
+///     .level(Priority::Level3, Scheduling::RoundRobin)
+///     .channels(Priority::Level3, Channels::CHAN0 | Channels::CHAN1)
+///     .level(Priority::Level0, Scheduling::Static)
+///     .channels(Priority::Level0, Channels::CHAN4 | Channels::CHAN2);
+///
+/// let order: Vec<_> = arbitration.order().map(|rank| rank.channels).collect();
+/// assert_eq!(order, [Channels::CHAN0 | Channels::CHAN1, Channels::CHAN2, Channels::CHAN4]);
+/// assert!(arbitration.starved().is_empty());
+/// ```
+///
+/// [`DMAController::apply_arbitration()`]: struct.DMAController.html#method.apply_arbitration
+/// [`DMAController::arbitration()`]: struct.DMAController.html#method.arbitration
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Arbitration {
+    levels: [Option<Scheduling>; 4],
+    channels: [Channels; 4],
+}
+
+impl Default for Arbitration {
+    fn default() -> Arbitration {
+        Arbitration::new()
+    }
+}
+
+impl Arbitration {
+    /// Create a scheme where every priority level is disabled, and no channel is assigned to a level.
+    pub fn new() -> Arbitration {
+        Arbitration {
+            levels: [None; 4],
+            channels: [Channels::empty(); 4],
+        }
+    }
+
+    /// Enable `level` with the given scheduling.
+    pub fn level(mut self, level: Priority, scheduling: Scheduling) -> Arbitration {
+        self.levels[level as usize] = Some(scheduling);
+        self
+    }
+
+    /// Disable `level`. Its channels are never granted access while it is disabled.
+    pub fn disable_level(mut self, level: Priority) -> Arbitration {
+        self.levels[level as usize] = None;
+        self
+    }
+
+    /// Assign `channels` to `level`, removing them from any other level.
+    pub fn channels(mut self, level: Priority, channels: Channels) -> Arbitration {
+        for assigned in self.channels.iter_mut() {
+            assigned.remove(channels);
+        }
+        self.channels[level as usize].insert(channels);
+        self
+    }
+
+    /// Assign the channel with the given ID to `level`, removing it from any other level.
+    pub fn channel(self, id: u8, level: Priority) -> Arbitration {
+        self.channels(level, Channels::from_bits_truncate(1 << id))
+    }
+
+    /// Get the scheduling of `level`, or `None` if the level is disabled.
+    pub fn get_scheduling(&self, level: Priority) -> Option<Scheduling> {
+        self.levels[level as usize]
+    }
+
+    /// Get the channels assigned to `level`.
+    pub fn get_channels(&self, level: Priority) -> Channels {
+        self.channels[level as usize]
+    }
+
+    /// Get the channels assigned to a disabled level, which are never granted access.
+    pub fn starved(&self) -> Channels {
+        (0..4).filter(|&i| self.levels[i].is_none())
+            .fold(Channels::empty(), |starved, i| starved | self.channels[i])
+    }
+
+    /// Get the effective arbitration order, from the first channels granted access to the last.
+    ///
+    /// Higher priority levels are served first. Within a level with static scheduling, each channel has its own rank,
+    /// with lower channel numbers ranked first. Within a level with round-robin scheduling, all channels share a
+    /// single rank. Channels on disabled levels are left out.
+    pub fn order(&self) -> ArbitrationOrder {
+        ArbitrationOrder {
+            arbitration: *self,
+            level: 4,
+            remaining: Channels::empty(),
+        }
+    }
+}
+
+/// A set of channels that are granted access to the DMA system on an equal footing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ArbitrationRank {
+    /// The priority level of the channels.
+    pub level: Priority,
+    /// The channels sharing the rank.
+    pub channels: Channels,
+}
+
+/// Iterator over the effective arbitration order, created by [`Arbitration::order()`].
+///
+/// [`Arbitration::order()`]: struct.Arbitration.html#method.order
+pub struct ArbitrationOrder {
+    arbitration: Arbitration,
+    level: usize,
+    remaining: Channels,
+}
+
+impl Iterator for ArbitrationOrder {
+    type Item = ArbitrationRank;
+
+    fn next(&mut self) -> Option<ArbitrationRank> {
+        while self.remaining.is_empty() {
+            if self.level == 0 {
+                return None;
+            }
+            self.level -= 1;
+            if self.arbitration.levels[self.level].is_some() {
+                self.remaining = self.arbitration.channels[self.level];
+            }
+        }
+
+        let channels = match self.arbitration.levels[self.level] {
+            Some(Scheduling::Static) => {
+                let bits = self.remaining.bits();
+                Channels::from_bits_truncate(bits & bits.wrapping_neg())
+            },
+            _ => self.remaining,
+        };
+        self.remaining.remove(channels);
+        Some(ArbitrationRank {
+            level: LEVELS[self.level],
+            channels,
+        })
+    }
+}
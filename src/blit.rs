@@ -0,0 +1,263 @@
+use core::fmt;
+use crate::{Channel, TriggerSource, TriggerAction, TransferDescriptor, StepSize, Beat};
+
+/// Error type for the kinds of errors that can occur while building a [`Blit2D`] descriptor chain.
+///
+/// [`Blit2D`]: struct.Blit2D.html
+#[derive(Debug)]
+pub enum BlitError {
+    /// The rectangle is empty, wider than the pitch of a buffer, or needs a block longer than 65535 beats.
+    InvalidGeometry,
+    /// Not enough descriptors were provided to build the descriptor chain.
+    TooFewDescriptors {
+        /// The amount of descriptors needed, including the first descriptor of the channel.
+        needed: usize,
+    },
+}
+
+impl fmt::Display for BlitError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlitError::InvalidGeometry => write!(w, "The rectangle doesn't fit the buffers or a block transfer."),
+            BlitError::TooFewDescriptors { needed } => write!(w, "The blit needs {} descriptors.", needed),
+        }
+    }
+}
+
+/// One side of a 2D transfer.
+pub enum Surface<T> {
+    /// A rectangle in a buffer, starting at `addr`, where each row starts `pitch` elements after the previous one.
+    Buffer {
+        /// Address of the top-left element of the rectangle.
+        addr: *const T,
+        /// Distance between the start of two rows, in elements.
+        pitch: usize,
+    },
+    /// A fixed address, such as a display data register or a fill pattern.
+    Fixed(*const T),
+}
+
+impl<T> Surface<T> {
+    fn pitch(&self, width: usize) -> Option<usize> {
+        match self {
+            Surface::Buffer { pitch, .. } if *pitch != width => Some(*pitch),
+            _ => None,
+        }
+    }
+
+    fn addr(&self, offset: usize) -> *const () {
+        match self {
+            Surface::Buffer { addr, .. } => addr.wrapping_add(offset) as *const (),
+            Surface::Fixed(addr) => *addr as *const (),
+        }
+    }
+
+    fn is_fixed(&self) -> bool {
+        match self {
+            Surface::Fixed(_) => true,
+            Surface::Buffer { .. } => false,
+        }
+    }
+}
+
+/// How the rectangle is split into block transfers.
+enum Layout {
+    /// Every buffer holds the rectangle contiguously, so blocks cover as many elements as possible.
+    Contiguous,
+    /// One block per column, stepping over the rows of the source (if `true`) or destination buffer.
+    Columns(StepSize, bool),
+    /// One block per row.
+    Rows,
+}
+
+/// Rectangular 2D transfer between framebuffers and peripherals.
+///
+/// A blit copies a rectangle of `width` by `height` elements, where either side can be a rectangle in a larger
+/// buffer, or a fixed address. The rectangle is moved row by row, unless it can be moved with fewer blocks:
+///
+/// * If both buffers hold the rectangle contiguously, the rectangle is moved as one run of elements.
+/// * If one buffer has a pitch of 2 to 128 elements, and the rectangle is a single column or a fill from a fixed
+///   address, each column is moved as one block by stepping over the rows with `StepSize`.
+///
+/// Blits between buffers run as a single software triggered transaction. On SAMD5x, a blit into a peripheral register
+/// should be paced by the peripheral instead, with [`with_trigger`], so each element is moved on a trigger from the
+/// peripheral rather than at bus speed.
+///
+/// Call [`descriptors_needed`] to find out how many descriptors to provide for the chain.
+///
+/// [`with_trigger`]: #method.with_trigger
+/// [`descriptors_needed`]: #method.descriptors_needed
+pub struct Blit2D<T: Beat> {
+    src: Surface<T>,
+    dst: Surface<T>,
+    width: usize,
+    height: usize,
+    #[cfg(feature = "samd5x")]
+    trigger: Option<TriggerSource>,
+}
+
+impl<T: Beat> Blit2D<T> {
+    /// Create a blit from `src` to `dst` of a rectangle of `width` by `height` elements.
+    pub fn new(src: Surface<T>, dst: Surface<T>, width: usize, height: usize) -> Blit2D<T> {
+        Blit2D {
+            src,
+            dst,
+            width,
+            height,
+            #[cfg(feature = "samd5x")]
+            trigger: None,
+        }
+    }
+
+    /// Move a single element on each trigger from `source`, such as the TX trigger of the SERCOM driving a display.
+    #[cfg(feature = "samd5x")]
+    pub fn with_trigger(mut self, source: TriggerSource) -> Blit2D<T> {
+        self.trigger = Some(source);
+        self
+    }
+
+    /// Create a blit copying a rectangle between two framebuffers.
+    pub fn copy(src: *const T, src_pitch: usize, dst: *mut T, dst_pitch: usize, width: usize,
+        height: usize) -> Blit2D<T>
+    {
+        Blit2D::new(Surface::Buffer { addr: src, pitch: src_pitch },
+            Surface::Buffer { addr: dst, pitch: dst_pitch }, width, height)
+    }
+
+    /// Create a blit filling a rectangle of a framebuffer with `pattern`.
+    pub fn fill(pattern: &'static T, dst: *mut T, dst_pitch: usize, width: usize, height: usize) -> Blit2D<T> {
+        Blit2D::new(Surface::Fixed(pattern), Surface::Buffer { addr: dst, pitch: dst_pitch }, width, height)
+    }
+
+    /// Create a blit streaming a rectangle of a framebuffer, row by row, into the peripheral register at `reg`.
+    ///
+    /// One element is moved on each trigger from `trigger`, which should be raised when the peripheral is ready to
+    /// accept new data.
+    #[cfg(feature = "samd5x")]
+    pub fn to_peripheral(src: *const T, src_pitch: usize, reg: *mut T, width: usize, height: usize,
+        trigger: TriggerSource) -> Blit2D<T>
+    {
+        Blit2D::new(Surface::Buffer { addr: src, pitch: src_pitch }, Surface::Fixed(reg), width, height)
+            .with_trigger(trigger)
+    }
+
+    /// Return the amount of descriptors needed for the blit, including the first descriptor of the channel.
+    pub fn descriptors_needed(&self) -> usize {
+        match self.layout() {
+            Layout::Contiguous => (self.width * self.height).div_ceil(u16::MAX as usize),
+            Layout::Columns(..) => self.width,
+            Layout::Rows => self.height,
+        }
+    }
+
+    /// Build the descriptor chain for the blit into `channel`, using its first descriptor and as many of
+    /// `descriptors` as needed.
+    ///
+    /// The channel is configured for a software triggered transaction, or for one beat per trigger if the blit has a
+    /// trigger source, and is left disabled. Enable the channel, and trigger it for a software triggered blit, to
+    /// start the blit.
+    pub fn program(&self, channel: &mut Channel, descriptors: &mut [TransferDescriptor]) -> Result<(), BlitError> {
+        self.check_geometry()?;
+        let needed = self.descriptors_needed();
+        if descriptors.len() + 1 < needed {
+            return Err(BlitError::TooFewDescriptors { needed });
+        }
+
+        channel.disable();
+        #[cfg(feature = "samd5x")]
+        let trigger = self.trigger;
+        #[cfg(feature = "samd21")]
+        let trigger = None;
+        match trigger {
+            Some(source) => channel.set_beat_trigger(source),
+            None => {
+                channel.set_source(TriggerSource::Disable);
+                channel.set_trigger_action(TriggerAction::Transaction);
+            },
+        }
+
+        let layout = self.layout();
+        let total = self.width * self.height;
+        let slots = channel.chain_slots(descriptors);
+        for i in 0..needed {
+            let desc = unsafe { &mut *slots.get(i) };
+            *desc = Default::default();
+            desc.set_beat_size(T::SIZE);
+            desc.set_src_addr_increment(!self.src.is_fixed());
+            desc.set_dest_addr_increment(!self.dst.is_fixed());
+
+            let (beats, src_offset, dst_offset) = match layout {
+                Layout::Contiguous => {
+                    let offset = i * u16::MAX as usize;
+                    ((total - offset).min(u16::MAX as usize), offset, offset)
+                },
+                Layout::Columns(step, src_stepped) => {
+                    desc.set_step_size(step);
+                    desc.set_step_selection(src_stepped);
+                    (self.height, i, i)
+                },
+                Layout::Rows => {
+                    let src_pitch = self.src.pitch(self.width).unwrap_or(self.width);
+                    let dst_pitch = self.dst.pitch(self.width).unwrap_or(self.width);
+                    (self.width, i * src_pitch, i * dst_pitch)
+                },
+            };
+            desc.set_block_count(beats as u16);
+            desc.set_src_start_addr(self.src.addr(src_offset));
+            desc.set_dst_start_addr(self.dst.addr(dst_offset));
+            if i + 1 < needed {
+                desc.set_next_desc_addr(slots.get(i + 1));
+            }
+            desc.set_valid();
+        }
+        Ok(())
+    }
+
+    fn check_geometry(&self) -> Result<(), BlitError> {
+        let fits = |surface: &Surface<T>| match surface {
+            Surface::Buffer { pitch, .. } => *pitch >= self.width,
+            Surface::Fixed(_) => true,
+        };
+        let block_len = match self.layout() {
+            Layout::Contiguous => 1,
+            Layout::Columns(..) => self.height,
+            Layout::Rows => self.width,
+        };
+        if self.width == 0 || self.height == 0 || !fits(&self.src) || !fits(&self.dst)
+            || block_len > u16::MAX as usize
+        {
+            return Err(BlitError::InvalidGeometry);
+        }
+        Ok(())
+    }
+
+    fn layout(&self) -> Layout {
+        if self.height == 1 {
+            return Layout::Contiguous;
+        }
+        match (self.src.pitch(self.width), self.dst.pitch(self.width)) {
+            (None, None) => Layout::Contiguous,
+            (Some(pitch), None) if self.width == 1 => {
+                step_size(pitch).map_or(Layout::Rows, |step| Layout::Columns(step, true))
+            },
+            (None, Some(pitch)) if self.width == 1 || (self.src.is_fixed() && self.width < self.height) => {
+                step_size(pitch).map_or(Layout::Rows, |step| Layout::Columns(step, false))
+            },
+            _ => Layout::Rows,
+        }
+    }
+}
+
+/// Return the step size covering `pitch` elements, if there is one.
+fn step_size(pitch: usize) -> Option<StepSize> {
+    Some(match pitch {
+        2 => StepSize::X2,
+        4 => StepSize::X4,
+        8 => StepSize::X8,
+        16 => StepSize::X16,
+        32 => StepSize::X32,
+        64 => StepSize::X64,
+        128 => StepSize::X128,
+        _ => return None,
+    })
+}
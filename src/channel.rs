@@ -1,9 +1,20 @@
 use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::ptr;
+use core::task::{Context, Poll};
 use crate::target_device::DMAC;
-use crate::{TriggerSource, TriggerAction, Priority, Interrupts};
+use crate::{TriggerSource, TriggerAction, Priority, Interrupts, Beat, EventAction, EventConfig};
+#[cfg(feature = "samd5x")]
+use crate::EventOutputMode;
 #[cfg(feature = "samd5x")]
 use crate::{BurstLength, FifoThreshold};
 use crate::descriptors::{TransferDescriptor};
+use crate::config::{ChannelConfig, ConfigError};
+use crate::snapshot::{ChannelSnapshot, ChannelStatus};
+use crate::deadline::Deadline;
+#[cfg(feature = "stats")]
+use crate::stats;
 
 #[cfg(feature = "samd5x")]
 macro_rules! channel_reg {
@@ -25,7 +36,7 @@ macro_rules! channel_reg {
 }
 
 /// Error type for the kinds of errors that can occur during a transaction.
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TransactionError {
     /// An invalid descriptor was fetched from memory.
     InvalidDescriptor,
@@ -46,7 +57,79 @@ impl fmt::Display for TransactionError {
     }
 }
 
+/// Error type for the kinds of errors that can occur while waiting for a channel.
+#[derive(Debug)]
+pub enum WaitError {
+    /// The transaction ended with an error.
+    Transaction(TransactionError),
+    /// The deadline expired before the transaction ended. The channel has been suspended or disabled, and 
+    /// `remaining` beats of the current block were not transferred.
+    Timeout {
+        /// The block transfer count of the write-back descriptor.
+        remaining: u16,
+    },
+}
+
+impl From<TransactionError> for WaitError {
+    fn from(err: TransactionError) -> WaitError {
+        WaitError::Transaction(err)
+    }
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WaitError::Transaction(err) => err.fmt(w),
+            WaitError::Timeout { remaining } => {
+                write!(w, "The transaction timed out with {} beats remaining in the block.", remaining)
+            },
+        }
+    }
+}
+
+/// Error type for the kinds of errors that can occur while issuing a channel command.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The channel is disabled, so it can't be suspended or resumed.
+    ChannelDisabled,
+    /// A previous command has not been acted upon yet.
+    CommandPending,
+    /// The channel is not suspended.
+    NotSuspended,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::ChannelDisabled => write!(w, "The channel is disabled."),
+            CommandError::CommandPending => write!(w, "A previous command has not been acted upon yet."),
+            CommandError::NotSuspended => write!(w, "The channel is not suspended."),
+        }
+    }
+}
+
+/// The state of the software command of a channel.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CommandState {
+    /// No command is pending.
+    Idle,
+    /// A suspend command has been issued, but the channel has not been suspended yet.
+    Suspending,
+    /// A resume command has been issued, but the channel has not resumed yet.
+    Resuming,
+}
+
+/// What happens to the channel when a wait times out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeoutAction {
+    /// Suspend the channel, so the transaction can be resumed later.
+    Suspend,
+    /// Disable the channel, aborting the transaction.
+    Disable,
+}
+
 /// The return value of `Transaction::try_wait()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum WaitResult {
     /// The transaction has ended or been aborted.
     Done,
@@ -56,6 +139,252 @@ pub enum WaitResult {
     Ongoing,
 }
 
+/// Progress of the transaction on a channel, returned by [`Channel::progress()`].
+/// 
+/// [`Channel::progress()`]: struct.Channel.html#method.progress
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TransferProgress {
+    /// Index of the descriptor being transferred in the chain, where `0` is the first descriptor of the channel.
+    pub descriptor_index: usize,
+    /// Beats left to transfer in the current block.
+    pub beats_remaining: u16,
+    /// Bytes transferred so far, over every block of the chain up to the current one.
+    pub bytes_done: usize,
+}
+
+/// The state of a transaction aborted by [`Channel::abort()`].
+/// 
+/// [`Channel::abort()`]: struct.Channel.html#method.abort
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AbortReport {
+    /// Where the transaction stopped.
+    pub progress: TransferProgress,
+    /// Beats transferred from the current block before the channel stopped.
+    pub beats_transferred: u16,
+    /// The interrupt flags that were pending when the channel stopped. They have been cleared.
+    pub flags: Interrupts,
+}
+
+/// The outcome of recovering a channel with [`Channel::recover()`].
+/// 
+/// [`Channel::recover()`]: struct.Channel.html#method.recover
+#[derive(Debug)]
+pub struct RecoveryReport {
+    /// The error the channel stopped on, if any.
+    pub error: Option<TransactionError>,
+    /// The descriptor that caused the error: the invalid descriptor for a fetch error, or the descriptor being 
+    /// transferred for a transfer or CRC error.
+    pub descriptor: Option<*mut TransferDescriptor>,
+    /// The index of the faulting descriptor in the chain, where `0` is the first descriptor of the channel, if it 
+    /// is part of the chain.
+    pub descriptor_index: Option<usize>,
+    /// Where the transaction stopped.
+    pub progress: TransferProgress,
+    /// Whether the channel had to be reset to clear its error status. Its configuration has been restored.
+    pub reset: bool,
+}
+
+/// An ongoing memory fill, created by [`Channel::fill()`].
+/// 
+/// The fill pattern is kept in the first element of the buffer, which the DMA system copies into the rest of the 
+/// buffer. Buffers longer than a single block transfer are filled in several transactions, which are started as the 
+/// fill is polled.
+/// 
+/// [`Channel::fill()`]: struct.Channel.html#method.fill
+pub struct Fill<'a, T: Beat + 'static> {
+    channel: &'a mut Channel,
+    buffer: &'static mut [T],
+    next: usize,
+}
+
+impl<'a, T: Beat + 'static> Fill<'a, T> {
+    /// Poll the fill, starting the next transaction if the previous one has completed.
+    /// 
+    /// Returns `Ok(true)` once the whole buffer has been filled.
+    pub fn is_done(&mut self) -> Result<bool, TransactionError> {
+        if self.channel.is_enabled() {
+            return Ok(false);
+        }
+
+        let intflag = self.channel.get_interrupt_flags();
+        self.channel.clear_interrupt_flags();
+        if intflag.intersects(Interrupts::TERR) {
+            return Err(TransactionError::TransferError);
+        }
+
+        if self.next < self.buffer.len() {
+            self.start_next();
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Block until the whole buffer has been filled.
+    pub fn wait(&mut self) -> Result<(), TransactionError> {
+        while !self.is_done()? {}
+        Ok(())
+    }
+
+    /// Return the buffer. If the fill is still ongoing, it is aborted.
+    pub fn free(self) -> &'static mut [T] {
+        self.channel.disable();
+        self.buffer
+    }
+
+    fn start_next(&mut self) {
+        let count = (self.buffer.len() - self.next).min(u16::MAX as usize);
+        let desc = self.channel.get_first_descriptor();
+        *desc = Default::default();
+        desc.set_beat_size(T::SIZE);
+        desc.set_block_count(count as u16);
+        desc.set_dest_addr_increment(true);
+        desc.set_src_addr(self.buffer.as_ptr() as *const ());
+        desc.set_dst_start_addr(self.buffer[self.next..].as_ptr() as *const ());
+        desc.set_valid();
+        self.next += count;
+
+        self.channel.enable();
+        self.channel.trigger();
+    }
+}
+
+/// A future suspending a channel, created by [`Channel::suspend()`].
+/// 
+/// The suspend command is issued when the future is first polled, and the future completes once the channel has 
+/// been suspended. The future wakes itself after every poll until then, so it doesn't depend on the channel interrupt.
+/// 
+/// [`Channel::suspend()`]: struct.Channel.html#method.suspend
+#[must_use = "the channel is not suspended until the future is polled, use `suspend_blocking()` to suspend it now"]
+pub struct Suspend<'a> {
+    channel: &'a mut Channel,
+    issued: bool,
+}
+
+impl<'a> Future for Suspend<'a> {
+    type Output = Result<(), CommandError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if !this.issued {
+            this.channel.request_suspend()?;
+            this.issued = true;
+        }
+        match this.channel.suspend_state() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            },
+        }
+    }
+}
+
+/// Access to a suspended channel, created by [`Channel::suspended()`].
+/// 
+/// While the channel is suspended, the DMA system won't fetch the next descriptor of the chain, so it can be 
+/// rewritten safely. The access ends when the channel is resumed.
+/// 
+/// [`Channel::suspended()`]: struct.Channel.html#method.suspended
+pub struct SuspendedChannel<'a> {
+    channel: &'a mut Channel,
+}
+
+impl<'a> SuspendedChannel<'a> {
+    /// Get the suspended channel.
+    pub fn channel(&self) -> &Channel {
+        self.channel
+    }
+
+    /// Get a mutable reference to the descriptor that will be fetched when the channel resumes, as located by the 
+    /// write-back descriptor. Returns `None` if the current block is the last one of the chain.
+    pub fn next_descriptor(&mut self) -> Option<&mut TransferDescriptor> {
+        self.channel.read_writeback_descriptor().get_next_desc_addr().map(|desc| unsafe { &mut *desc })
+    }
+
+    /// Skip the block following the suspended one, by linking the write-back descriptor past the upcoming 
+    /// descriptor. Returns `false` if there is no upcoming descriptor to skip.
+    /// 
+    /// If the skipped descriptor was the last one of the chain, the transaction ends when the channel resumes.
+    /// 
+    /// # Example
+    /// 
+    /// The [`model`] keeps a write-back descriptor like the channel does, and skips blocks by rewriting it the same 
+    /// way, so a chain with a skipped block can be checked on a host.
+    /// 
+    /// ```
+    /// use samd_dma::{BeatSize, BlockAction, TransferDescriptor};
+    /// use samd_dma::model::{Model, Outcome};
+    /// 
+    /// let src: [u8; 3] = [1, 2, 3];
+    /// let mut dst = [0u8; 3];
+    /// let mut descriptors = [TransferDescriptor::new(); 3];
+    /// let next = descriptors.as_mut_ptr();
+    /// for (i, desc) in descriptors.iter_mut().enumerate() {
+    ///     desc.set_beat_size(BeatSize::Byte);
+    ///     desc.set_block_count(1);
+    ///     desc.set_src_start_addr(&src[i] as *const u8 as *const ());
+    ///     desc.set_dst_start_addr(&mut dst[i] as *mut u8 as *const ());
+    ///     if i < 2 {
+    ///         desc.set_next_desc_addr(unsafe { next.add(i + 1) });
+    ///     }
+    ///     desc.set_valid();
+    /// }
+    /// // Suspend after the first block, then skip the second block.
+    /// descriptors[0].set_block_action(BlockAction::Suspend);
+    /// 
+    /// let mut model = Model::new(&descriptors[0], 8);
+    /// assert_eq!(unsafe { model.run() }, Outcome::Suspended);
+    /// assert!(model.skip_next_block());
+    /// assert_eq!(unsafe { model.run() }, Outcome::Done);
+    /// assert_eq!(dst, [1, 0, 3]);
+    /// ```
+    /// 
+    /// [`model`]: model/index.html
+    pub fn skip_next_block(&mut self) -> bool {
+        let write_back = self.channel.write_back;
+        let mut current = unsafe { ptr::read_volatile(write_back) };
+        let skipped = current.link_past_next();
+        if skipped {
+            unsafe { ptr::write_volatile(write_back, current) };
+        }
+        skipped
+    }
+
+    /// Resume the channel, ending the access to its descriptors.
+    pub fn resume(self) -> Result<(), CommandError> {
+        self.channel.resume()
+    }
+}
+
+/// The first descriptor of a channel followed by a slice of extra descriptors, indexed as one chain.
+pub(crate) struct ChainSlots {
+    first: *mut TransferDescriptor,
+    rest: *mut TransferDescriptor,
+    len: usize,
+}
+
+impl ChainSlots {
+    /// Get the descriptor at `index`, where index 0 is the first descriptor of the channel.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `index` is out of bounds.
+    pub(crate) fn get(&self, index: usize) -> *mut TransferDescriptor {
+        assert!(index < self.len);
+        if index == 0 {
+            self.first
+        } else {
+            unsafe { self.rest.add(index - 1) }
+        }
+    }
+
+    /// Get the index of `desc` in the chain, if it is one of its descriptors.
+    #[cfg(feature = "samd5x")]
+    pub(crate) fn index_of(&self, desc: *const TransferDescriptor) -> Option<usize> {
+        (0..self.len).find(|&i| ptr::eq(self.get(i), desc))
+    }
+}
+
 /// DMA channel.
 /// 
 /// This structure represents a DMA channel. Using the [`start_transfer`] method, you can
@@ -66,6 +395,8 @@ pub struct Channel {
     id: u8,
     first_desc: *mut TransferDescriptor,
     write_back: *mut TransferDescriptor,
+    /// Whether the channel was seen suspended, as the `SUSP` flag may have been cleared since.
+    suspended: bool,
 }
 
 impl Channel {
@@ -76,6 +407,7 @@ impl Channel {
             id,
             first_desc,
             write_back,
+            suspended: false,
         }
     }
 
@@ -158,20 +490,174 @@ impl Channel {
         reg.read().trigsrc().variant().into()
     }
 
-    /// Set the priority level of the channel.
-    pub fn set_priority(&mut self, priority: Priority) {
+    /// Configure the channel to transfer a single beat for each trigger from `source`.
+    pub(crate) fn set_beat_trigger(&mut self, source: TriggerSource) {
+        self.set_source(source);
+        #[cfg(feature = "samd5x")]
+        {
+            self.set_burst_length(BurstLength::Single);
+            self.set_trigger_action(TriggerAction::Burst);
+        }
+        #[cfg(feature = "samd21")]
+        self.set_trigger_action(TriggerAction::Beat);
+    }
+
+    /// Set the event input and output settings of the channel.
+    pub fn set_event_config(&mut self, events: EventConfig) {
+        #[cfg(feature = "samd5x")]
+        channel_reg!(chevctrl, self.id).write(|w| unsafe {
+            w.evact().bits(events.action as u8)
+                .evomode().bits(events.output_mode as u8)
+                .evie().bit(events.input)
+                .evoe().bit(events.output)
+        });
+        #[cfg(feature = "samd21")]
+        channel_reg!(chctrlb, self.id).modify(|_, w| unsafe {
+            w.evact().bits(events.action as u8)
+                .evie().bit(events.input)
+                .evoe().bit(events.output)
+        });
+    }
+
+    /// Get the event input and output settings of the channel.
+    pub fn get_event_config(&self) -> EventConfig {
         #[cfg(feature = "samd5x")]
-        channel_reg!(chprilvl, self.id).write(|w| w.prilvl().bits(priority as u8));
+        {
+            let reg = channel_reg!(chevctrl, self.id).read();
+            EventConfig {
+                action: EventAction::from(reg.evact().bits()),
+                input: reg.evie().bit(),
+                output: reg.evoe().bit(),
+                output_mode: if reg.evomode().bits() == 0 {
+                    EventOutputMode::Block
+                } else {
+                    EventOutputMode::TriggerAction
+                },
+            }
+        }
         #[cfg(feature = "samd21")]
-        channel_reg!(chctrlb, self.id).modify(|_, w| w.lvl().bits(priority as u8))
+        {
+            let reg = channel_reg!(chctrlb, self.id).read();
+            EventConfig {
+                action: EventAction::from(reg.evact().bits()),
+                input: reg.evie().bit(),
+                output: reg.evoe().bit(),
+            }
+        }
+    }
+
+    /// Enable the event input of the channel with `action`, or disable it if `action` is `EventAction::NoAct`. The 
+    /// event output settings are left unchanged.
+    pub fn set_event_action(&mut self, action: EventAction) {
+        let mut events = self.get_event_config();
+        events.action = action;
+        events.input = action != EventAction::NoAct;
+        self.set_event_config(events);
+    }
+
+    /// Make transfers conditional on the event input: triggers only start a transfer once an event is received.
+    pub fn conditional_transfer(&mut self) {
+        self.set_event_action(EventAction::ConditionalTrigger);
+    }
+
+    /// Make blocks conditional on the event input: each block transfer only starts once an event is received.
+    pub fn conditional_block(&mut self) {
+        self.set_event_action(EventAction::ConditionalBlock);
+    }
+
+    /// Skip suspend block actions on the event input: each event received makes the channel carry on with the next 
+    /// block, instead of suspending, when a block with a suspend block action ends.
+    /// 
+    /// The [`model`] of the descriptor engine can simulate this event with `Model::skip_suspend()`.
+    /// 
+    /// [`model`]: model/index.html
+    pub fn skip_suspend_on_event(&mut self) {
+        self.set_event_action(EventAction::SkipSuspend);
+    }
+
+    /// Set the priority level of the channel.
+    pub fn set_priority(&mut self, priority: Priority) {
+        write_priority(self.id, priority);
     }
 
     /// Get channel priority level.
     pub fn get_priority(&self) -> Priority {
+        read_priority(self.id)
+    }
+
+    /// Capture the configuration of the channel, including its first transfer descriptor.
+    pub fn save_config(&self) -> ChannelConfig {
+        ChannelConfig {
+            source: self.get_source(),
+            trigger_action: self.get_trigger_action(),
+            priority: self.get_priority(),
+            #[cfg(feature = "samd5x")]
+            burst_length: self.get_burst_length(),
+            #[cfg(feature = "samd5x")]
+            fifo_threshold: self.get_fifi_threshold(),
+            #[cfg(feature = "samd5x")]
+            run_standby: self.get_run_standby(),
+            interrupts: self.get_enabled_interrupts(),
+            events: self.get_event_config(),
+            descriptor: *self.get_first_descriptor(),
+        }
+    }
+
+    /// Apply a configuration, including its first transfer descriptor, in a single call.
+    /// 
+    /// Each control register of the channel is written once, replacing every setting in it. Returns 
+    /// `ConfigError::ChannelEnabled` if the channel is enabled, as its control registers can't be changed.
+    pub fn configure(&mut self, config: &ChannelConfig) -> Result<(), ConfigError> {
+        if self.is_enabled() {
+            return Err(ConfigError::ChannelEnabled);
+        }
+
         #[cfg(feature = "samd5x")]
-        return channel_reg!(chprilvl, self.id).read().prilvl().variant().into();
+        {
+            channel_reg!(chctrla, self.id).write(|w| unsafe {
+                w.trigsrc().bits(config.source as u8)
+                    .trigact().bits(config.trigger_action as u8)
+                    .burstlen().bits(config.burst_length as u8)
+                    .threshold().bits(config.fifo_threshold as u8)
+                    .runstdby().bit(config.run_standby)
+            });
+            channel_reg!(chprilvl, self.id).write(|w| w.prilvl().bits(config.priority as u8));
+            channel_reg!(chevctrl, self.id).write(|w| unsafe {
+                w.evact().bits(config.events.action as u8)
+                    .evomode().bits(config.events.output_mode as u8)
+                    .evie().bit(config.events.input)
+                    .evoe().bit(config.events.output)
+            });
+            channel_reg!(chintenclr, self.id).write(|w| unsafe { w.bits(!config.interrupts.bits()) });
+            channel_reg!(chintenset, self.id).write(|w| unsafe { w.bits(config.interrupts.bits()) });
+        }
+
         #[cfg(feature = "samd21")]
-        return channel_reg!(chctrlb, self.id).read().lvl().variant().into();
+        {
+            // Select the channel once, then write the channel registers directly.
+            channel_reg!(chctrlb, self.id).write(|w| unsafe {
+                w.trigsrc().bits(config.source as u8)
+                    .trigact().bits(config.trigger_action as u8)
+                    .lvl().bits(config.priority as u8)
+                    .evact().bits(config.events.action as u8)
+                    .evie().bit(config.events.input)
+                    .evoe().bit(config.events.output)
+            });
+            let dmac = unsafe { &*DMAC::ptr() };
+            dmac.chintenclr.write(|w| unsafe { w.bits(!config.interrupts.bits()) });
+            dmac.chintenset.write(|w| unsafe { w.bits(config.interrupts.bits()) });
+        }
+
+        *self.get_first_descriptor() = config.descriptor;
+        Ok(())
+    }
+
+    /// Apply a configuration captured by [`save_config()`]. This is the same as [`configure()`].
+    /// 
+    /// [`save_config()`]: #method.save_config
+    /// [`configure()`]: #method.configure
+    pub fn restore_config(&mut self, config: &ChannelConfig) -> Result<(), ConfigError> {
+        self.configure(config)
     }
 
     /// Get a mutable reference to the first descriptor for the channel.
@@ -179,14 +665,65 @@ impl Channel {
         unsafe { &mut *self.first_desc }
     }
 
+    /// Index the first descriptor of the channel followed by `descriptors` as a single chain.
+    pub(crate) fn chain_slots(&self, descriptors: &mut [TransferDescriptor]) -> ChainSlots {
+        ChainSlots {
+            first: self.first_desc,
+            rest: descriptors.as_mut_ptr(),
+            len: descriptors.len() + 1,
+        }
+    }
+
+    /// Fill `buffer` with `value` using the DMA system, like a hardware `memset`.
+    /// 
+    /// The channel is reconfigured for software triggered transactions, and the first transaction is started 
+    /// immediately. The returned [`Fill`] must be polled to completion to fill buffers longer than a single block 
+    /// transfer.
+    /// 
+    /// [`Fill`]: struct.Fill.html
+    pub fn fill<T: Beat>(&mut self, buffer: &'static mut [T], value: T) -> Fill<'_, T> {
+        self.disable();
+        self.clear_interrupt_flags();
+        self.set_source(TriggerSource::Disable);
+        self.set_trigger_action(TriggerAction::Transaction);
+
+        let mut fill = Fill {
+            channel: self,
+            buffer,
+            next: 0,
+        };
+        if let Some(first) = fill.buffer.first_mut() {
+            *first = value;
+            fill.next = 1;
+        }
+        if fill.next < fill.buffer.len() {
+            fill.start_next();
+        }
+        fill
+    }
+
+    /// Fill `buffer` with zeroes using the DMA system. See [`fill()`] for details.
+    /// 
+    /// [`fill()`]: #method.fill
+    pub fn zero<T: Beat>(&mut self, buffer: &'static mut [T]) -> Fill<'_, T> {
+        self.fill(buffer, T::default())
+    }
+
     /// Get the channel's interrupt flags.
     pub fn get_interrupt_flags(&self) -> Interrupts {
         Interrupts::from_bits_truncate(channel_reg!(chintflag, self.id).read().bits())
     }
 
-    /// Reset the channel's interrupt flags.
+    /// Clear the channel's interrupt flags.
+    /// 
+    /// CHINTFLAG is write-one-to-clear, so a one is written to every flag.
     pub fn clear_interrupt_flags(&mut self) {
-        channel_reg!(chintflag, self.id).reset();
+        channel_reg!(chintflag, self.id).write(|w| w.terr().set_bit().tcmpl().set_bit().susp().set_bit());
+    }
+
+    /// Clear the given interrupt flags of the channel, leaving the others set.
+    pub(crate) fn clear_flags(&mut self, flags: Interrupts) {
+        channel_reg!(chintflag, self.id).write(|w| unsafe { w.bits(flags.bits()) });
     }
 
     /// Enable interrupts for the channel. Any interrupts that are not set will be disabled.
@@ -210,11 +747,39 @@ impl Channel {
         self.write_back 
     }
 
+    /// Read a copy of the descriptor at the Write-back Address of this channel.
+    /// 
+    /// The read is volatile, so the copy reflects the state the DMA engine last wrote back for this channel. The DMA 
+    /// system updates the write-back descriptor whenever the channel leaves the active state, such as at the end of a 
+    /// burst, so the block transfer count will lag behind an ongoing burst.
+    pub fn read_writeback_descriptor(&self) -> TransferDescriptor {
+        unsafe { ptr::read_volatile(self.write_back) }
+    }
+
+    /// Get the progress of the transaction, by matching the write-back descriptor against the descriptor chain that 
+    /// starts at the first descriptor of the channel.
+    /// 
+    /// The current descriptor is the one sharing its addresses and next descriptor address with the write-back 
+    /// descriptor. If no descriptor of the chain matches, such as before the channel has been started, the 
+    /// transaction is reported as not started. A chain that loops back to its first descriptor is walked once, so 
+    /// `bytes_done` restarts from zero on each lap. A chain that loops back to any other descriptor is walked until the 
+    /// loop is detected, so descriptors after the loop are never matched.
+    /// 
+    /// Like [`read_writeback_descriptor()`], the progress lags behind an ongoing burst.
+    /// 
+    /// [`read_writeback_descriptor()`]: #method.read_writeback_descriptor
+    pub fn progress(&self) -> TransferProgress {
+        chain_progress(self.first_desc, &self.read_writeback_descriptor())
+    }
+
     /// Enable the DMA channel.
     /// 
     /// After this call, this channel will be a part of the DMA arbitration scheme (if its corresponding priority level 
     /// is active), and trigger events will cause the transaction to start from the first descriptor.
     pub fn enable(&mut self) {
+        self.suspended = false;
+        #[cfg(feature = "stats")]
+        stats::record_running(self.id, true);
         channel_reg!(chctrla, self.id).modify(|_, w| w.enable().set_bit());
     }
 
@@ -237,40 +802,205 @@ impl Channel {
     /// 
     /// It is the responsibility of the caller to ensure that the call-site is in an interrupt-free section.
     pub fn trigger(&mut self) {
+        #[cfg(feature = "stats")]
+        stats::record_trigger(self.id);
         unsafe {&*DMAC::ptr()}.swtrigctrl.modify(|r, w| unsafe {
             w.bits(r.bits() | (1 << self.id))
         })
     }
 
-    /// Suspend the ongoing transaction. Returns `true` if the command was successfully written, `false` if another 
-    /// command is ongoing.
+    /// Get the state of the software command of the channel.
+    pub fn command_state(&self) -> CommandState {
+        let cmd = channel_reg!(chctrlb, self.id).read().cmd();
+        if cmd.is_suspend() {
+            CommandState::Suspending
+        } else if cmd.is_resume() {
+            CommandState::Resuming
+        } else {
+            CommandState::Idle
+        }
+    }
+
+    /// Issue a suspend command to the channel, and return immediately.
+    /// 
+    /// The suspend won't take effect until the ongoing burst transfer completes, at which point the `SUSP` interrupt 
+    /// flag is set. Use [`suspend_blocking()`] or [`suspend()`] to wait for it.
+    /// 
+    /// [`suspend_blocking()`]: #method.suspend_blocking
+    /// [`suspend()`]: #method.suspend
+    pub fn request_suspend(&mut self) -> Result<(), CommandError> {
+        self.command(|w| w.cmd().suspend())
+    }
+
+    /// Suspend the ongoing transaction, and block until the channel has been suspended.
     /// 
-    /// This call returns immediately, but the suspend operation won't complete until the ongoing burst transfer 
-    /// completes.
-    pub fn suspend(&mut self) -> bool {
-        let mut ongoing = true;
-        channel_reg!(chctrlb, self.id).modify(|r, w| {
-            if r.cmd().is_noact() {
-                w.cmd().suspend();
-                ongoing = false;
+    /// Returns `CommandError::ChannelDisabled` if the channel is disabled, or if the transaction ends before it could 
+    /// be suspended.
+    pub fn suspend_blocking(&mut self) -> Result<(), CommandError> {
+        self.request_suspend()?;
+        loop {
+            if let Some(result) = self.suspend_state() {
+                return result;
             }
-            w
-        });
-        ongoing
+        }
+    }
+
+    /// Suspend the ongoing transaction, returning a future that completes once the channel has been suspended.
+    /// 
+    /// Nothing happens until the future is polled, so calls that used to suspend the channel right away should use 
+    /// [`suspend_blocking()`] instead. See [`suspend_blocking()`] for the errors returned.
+    /// 
+    /// [`suspend_blocking()`]: #method.suspend_blocking
+    pub fn suspend(&mut self) -> Suspend<'_> {
+        Suspend {
+            channel: self,
+            issued: false,
+        }
+    }
+
+    /// Get access to the channel while it is suspended, to modify its upcoming descriptor before resuming it.
+    /// 
+    /// The channel is suspended once a [`suspend()`] has completed, or a block with a suspend block action has 
+    /// ended. This is detected from the `SUSP` interrupt flag, or from an earlier `WaitResult::Suspended` returned by 
+    /// [`poll_status()`] or [`wait_timeout()`], which clear the flag. Returns `CommandError::NotSuspended` otherwise.
+    /// 
+    /// ```no_run
+    /// use samd_dma::{Channel, TimeoutAction, WaitResult};
+    /// 
+    /// // Wait for the block with a suspend block action, then skip the block after it.
+    /// fn skip_after_suspend(channel: &mut Channel) {
+    ///     if let Ok(WaitResult::Suspended) = channel.wait_timeout(|| false, TimeoutAction::Disable) {
+    ///         let mut suspended = channel.suspended().unwrap();
+    ///         suspended.skip_next_block();
+    ///         suspended.resume().unwrap();
+    ///     }
+    /// }
+    /// ```
+    /// 
+    /// [`suspend()`]: #method.suspend
+    /// [`poll_status()`]: #method.poll_status
+    /// [`wait_timeout()`]: #method.wait_timeout
+    pub fn suspended(&mut self) -> Result<SuspendedChannel<'_>, CommandError> {
+        if !self.is_enabled() || !(self.suspended || self.get_interrupt_flags().intersects(Interrupts::SUSP)) {
+            return Err(CommandError::NotSuspended);
+        }
+        Ok(SuspendedChannel {
+            channel: self,
+        })
     }
 
-    /// Resume the ongoing transaction. Returns `true` if command was successfully written, `false` if another command 
-    /// is ongoing.
-    pub fn resume(&mut self) -> bool {
-        let mut ongoing = true;
-        channel_reg!(chctrlb, self.id).modify(|r, w| {
-            if r.cmd().is_noact() {
-                w.cmd().resume();
-                ongoing = false;
+    /// Resume a suspended transaction.
+    pub fn resume(&mut self) -> Result<(), CommandError> {
+        self.command(|w| w.cmd().resume())?;
+        self.suspended = false;
+        Ok(())
+    }
+
+    /// Write a command to CHCTRLB, clearing the `SUSP` flag so the completion of a suspend can be detected.
+    #[cfg(feature = "samd5x")]
+    fn command<F>(&mut self, cmd: F) -> Result<(), CommandError>
+        where F: FnOnce(&mut crate::target_device::dmac::channel::chctrlb::W)
+            -> &mut crate::target_device::dmac::channel::chctrlb::W
+    {
+        self.check_command()?;
+        channel_reg!(chintflag, self.id).write(|w| w.susp().set_bit());
+        channel_reg!(chctrlb, self.id).modify(|_, w| cmd(w));
+        Ok(())
+    }
+
+    /// Write a command to CHCTRLB, clearing the `SUSP` flag so the completion of a suspend can be detected.
+    #[cfg(feature = "samd21")]
+    fn command<F>(&mut self, cmd: F) -> Result<(), CommandError>
+        where F: FnOnce(&mut crate::target_device::dmac::chctrlb::W) -> &mut crate::target_device::dmac::chctrlb::W
+    {
+        self.check_command()?;
+        channel_reg!(chintflag, self.id).write(|w| w.susp().set_bit());
+        channel_reg!(chctrlb, self.id).modify(|_, w| cmd(w));
+        Ok(())
+    }
+
+    fn check_command(&self) -> Result<(), CommandError> {
+        if !self.is_enabled() {
+            return Err(CommandError::ChannelDisabled);
+        }
+        if self.command_state() != CommandState::Idle {
+            return Err(CommandError::CommandPending);
+        }
+        Ok(())
+    }
+
+    /// Return the result of a suspend command, or `None` if the channel has not been suspended yet.
+    fn suspend_state(&mut self) -> Option<Result<(), CommandError>> {
+        if self.get_interrupt_flags().intersects(Interrupts::SUSP) {
+            self.suspended = true;
+            Some(Ok(()))
+        } else if !self.is_enabled() {
+            Some(Err(CommandError::ChannelDisabled))
+        } else {
+            None
+        }
+    }
+
+    /// Block until the ongoing transaction ends or is suspended, or `deadline` expires.
+    /// 
+    /// Returns `WaitResult::Done` once the channel has been disabled at the end of the transaction, or 
+    /// `WaitResult::Suspended` if a block suspend action suspended the channel. The channel is then left suspended.
+    /// 
+    /// If the deadline expires, the channel is suspended or disabled according to `action`, and this call blocks until 
+    /// the ongoing burst transfer completes and the channel has stopped. `WaitError::Timeout` is then returned with 
+    /// the beats remaining in the current block, as read from the write-back descriptor. The channel is checked once 
+    /// more after the deadline expires, so a transaction that ends just in time is not reported as timed out.
+    /// 
+    /// The interrupt flags of the channel are used to detect errors, so this should not be used while the channel 
+    /// interrupts are handled elsewhere.
+    pub fn wait_timeout<D: Deadline>(&mut self, mut deadline: D, action: TimeoutAction)
+        -> Result<WaitResult, WaitError>
+    {
+        loop {
+            let expired = deadline.expired();
+            let intflag = self.get_interrupt_flags();
+            if intflag.intersects(Interrupts::TERR) {
+                self.clear_flags(intflag);
+                #[cfg(feature = "samd5x")]
+                if channel_reg!(chstatus, self.id).read().crcerr().bit_is_set() {
+                    return Err(TransactionError::CRCError.into());
+                }
+                return Err(TransactionError::TransferError.into());
             }
-            w
-        });
-        ongoing
+            if intflag.intersects(Interrupts::SUSP) {
+                self.clear_flags(intflag);
+                if channel_reg!(chstatus, self.id).read().ferr().bit_is_set() {
+                    return Err(TransactionError::InvalidDescriptor.into());
+                }
+                self.suspended = true;
+                return Ok(WaitResult::Suspended);
+            }
+            if !self.is_enabled() {
+                return Ok(WaitResult::Done);
+            }
+            if expired {
+                break;
+            }
+        }
+
+        match action {
+            TimeoutAction::Suspend => {
+                // If the channel can't take a suspend command, stop it rather than wait for another command.
+                if self.request_suspend().is_err() {
+                    self.disable();
+                }
+                // Also stop waiting once the command is no longer pending, in case a block action suspended the
+                // channel just before the command, and SUSP is not raised again.
+                while self.suspend_state().is_none() && self.command_state() == CommandState::Suspending {}
+            },
+            TimeoutAction::Disable => {
+                self.disable();
+                while self.is_enabled() {}
+            },
+        }
+        Err(WaitError::Timeout {
+            remaining: self.read_writeback_descriptor().get_block_transfer_count(),
+        })
     }
 
     /// Disable the channel. This aborts any ongoing transaction.
@@ -278,9 +1008,103 @@ impl Channel {
     /// This call returns immediately, but the transaction will not be aborted until
     /// the ongoing burst transfer completes.
     pub fn disable(&mut self) {
+        self.suspended = false;
+        #[cfg(feature = "stats")]
+        stats::record_running(self.id, false);
         channel_reg!(chctrla, self.id).modify(|_, w| w.enable().clear_bit());
     }
 
+    /// Abort the ongoing transaction, and block until the channel has stopped.
+    /// 
+    /// Unlike [`disable()`], this waits for the ongoing burst transfer to complete, so the write-back descriptor holds 
+    /// the final state of the transaction when it is read. The interrupt flags of the channel are cleared, so the 
+    /// next transaction starts clean.
+    /// 
+    /// [`disable()`]: #method.disable
+    pub fn abort(&mut self) -> AbortReport {
+        self.disable();
+        while self.is_enabled() {}
+
+        let progress = self.progress();
+        let desc = unsafe { &*self.descriptor_at(progress.descriptor_index) };
+        let flags = self.get_interrupt_flags();
+        // The channel has stopped, so no flag is raised again once cleared. Read the flags back to confirm it.
+        while !self.get_interrupt_flags().is_empty() {
+            self.clear_interrupt_flags();
+        }
+        AbortReport {
+            progress,
+            beats_transferred: desc.get_block_transfer_count() - progress.beats_remaining,
+            flags,
+        }
+    }
+
+    /// Recover the channel after a fetch error, transfer error or CRC error, leaving it disabled and ready for a new 
+    /// transfer.
+    /// 
+    /// The channel is aborted as with [`abort()`], and the descriptor that caused the error is located through the 
+    /// write-back descriptor, so it can be fixed before the channel is enabled again. If the error status of the 
+    /// channel is still set once it has stopped, the channel is reset and its configuration restored.
+    /// 
+    /// [`abort()`]: #method.abort
+    pub fn recover(&mut self) -> RecoveryReport {
+        let status = read_snapshot(self.id).status;
+        #[cfg(feature = "samd5x")]
+        let crc_error = status.intersects(ChannelStatus::CRCERR);
+        #[cfg(feature = "samd21")]
+        let crc_error = false;
+        let abort = self.abort();
+
+        let error = if status.intersects(ChannelStatus::FERR) {
+            Some(TransactionError::InvalidDescriptor)
+        } else if crc_error {
+            Some(TransactionError::CRCError)
+        } else if abort.flags.intersects(Interrupts::TERR) {
+            Some(TransactionError::TransferError)
+        } else {
+            None
+        };
+        let descriptor = match error {
+            Some(TransactionError::InvalidDescriptor) => {
+                if unsafe { (*self.first_desc).is_valid() } {
+                    self.read_writeback_descriptor().get_next_desc_addr()
+                } else {
+                    Some(self.first_desc)
+                }
+            },
+            Some(_) => Some(self.descriptor_at(abort.progress.descriptor_index)),
+            None => None,
+        };
+        let descriptor_index = descriptor.and_then(|desc| self.index_of(desc));
+
+        let reset = !read_snapshot(self.id).status.is_empty();
+        if reset {
+            let config = self.save_config();
+            self.reset();
+            while channel_reg!(chctrla, self.id).read().swrst().bit_is_set() {}
+            // The channel is disabled, so the configuration is always accepted.
+            let _ = self.configure(&config);
+        }
+
+        RecoveryReport {
+            error,
+            descriptor,
+            descriptor_index,
+            progress: abort.progress,
+            reset,
+        }
+    }
+
+    /// Get the descriptor at `index` in the chain starting at the first descriptor of the channel.
+    fn descriptor_at(&self, index: usize) -> *mut TransferDescriptor {
+        ChainWalk::new(self.first_desc).nth(index).unwrap_or(self.first_desc)
+    }
+
+    /// Get the index of `target` in the chain starting at the first descriptor of the channel.
+    fn index_of(&self, target: *mut TransferDescriptor) -> Option<usize> {
+        ChainWalk::new(self.first_desc).position(|desc| desc == target)
+    }
+
     /// Returns `true` if a transfer is pending on the channel.
     /// Returns `false` if a channel trigger action is completed, a bus error is detected, or the channel is disabled.
     pub fn is_pending(&self) -> bool {
@@ -302,29 +1126,152 @@ impl Channel {
     /// Any errors will be returned as `Err(TransactionError)`.
     pub fn poll_status(&mut self) -> Result<WaitResult, TransactionError> {
         let intflag = self.get_interrupt_flags();
-        let status = channel_reg!(chstatus, self.id).read();
-        self.clear_interrupt_flags();
+        let status = read_snapshot(self.id).status;
+        // Only clear the flags handled here, so a flag raised in the meantime is neither lost nor counted twice.
+        self.clear_flags(intflag);
+        #[cfg(feature = "stats")]
+        record_stats(self.id, self.first_desc, self.write_back, intflag, status);
 
-        if channel_reg!(chctrla, self.id).read().enable().bit_is_set() {
-            if intflag.intersects(Interrupts::TERR) {
-                #[cfg(feature = "samd5x")]
-                if status.crcerr().bit_is_set() {
-                    return Err(TransactionError::CRCError);
-                }
-                return Err(TransactionError::TransferError);
-            } else {
-                return Ok(WaitResult::Done);
-            }
+        let result = transaction_status(intflag, status, self.is_enabled());
+        if let Ok(WaitResult::Suspended) = result {
+            self.suspended = true;
         }
+        result
+    } 
+}
 
-        if intflag.intersects(Interrupts::SUSP) {
-            if status.ferr().bit_is_set() {
-                return Err(TransactionError::InvalidDescriptor);
-            } else {
-                return Ok(WaitResult::Suspended)
-            }
+/// Get the status of a transaction from the interrupt flags handled for its channel, the channel status, and whether 
+/// the channel is still enabled.
+pub(crate) fn transaction_status(flags: Interrupts, status: ChannelStatus, enabled: bool)
+    -> Result<WaitResult, TransactionError>
+{
+    if flags.intersects(Interrupts::TERR) {
+        #[cfg(feature = "samd5x")]
+        if status.intersects(ChannelStatus::CRCERR) {
+            return Err(TransactionError::CRCError);
         }
+        return Err(TransactionError::TransferError);
+    }
 
-        Ok(WaitResult::Ongoing)
-    } 
+    if flags.intersects(Interrupts::SUSP) {
+        if status.intersects(ChannelStatus::FERR) {
+            return Err(TransactionError::InvalidDescriptor);
+        } else {
+            return Ok(WaitResult::Suspended)
+        }
+    }
+
+    if !enabled {
+        return Ok(WaitResult::Done);
+    }
+
+    Ok(WaitResult::Ongoing)
+}
+
+/// Set the priority level of the channel with the given ID.
+pub(crate) fn write_priority(id: u8, priority: Priority) {
+    #[cfg(feature = "samd5x")]
+    channel_reg!(chprilvl, id).write(|w| w.prilvl().bits(priority as u8));
+    #[cfg(feature = "samd21")]
+    channel_reg!(chctrlb, id).modify(|_, w| w.lvl().bits(priority as u8))
+}
+
+/// Get the priority level of the channel with the given ID.
+pub(crate) fn read_priority(id: u8) -> Priority {
+    #[cfg(feature = "samd5x")]
+    return channel_reg!(chprilvl, id).read().prilvl().variant().into();
+    #[cfg(feature = "samd21")]
+    return channel_reg!(chctrlb, id).read().lvl().variant().into();
+}
+
+/// Capture the status and interrupt flags of the channel with the given ID.
+pub(crate) fn read_snapshot(id: u8) -> ChannelSnapshot {
+    ChannelSnapshot {
+        status: ChannelStatus::from_bits_truncate(channel_reg!(chstatus, id).read().bits()),
+        flags: Interrupts::from_bits_truncate(channel_reg!(chintflag, id).read().bits()),
+    }
+}
+
+/// Get the progress of the descriptor chain starting at `first`, given the write-back descriptor `current`.
+fn chain_progress(first: *mut TransferDescriptor, current: &TransferDescriptor) -> TransferProgress {
+    let mut bytes_done = 0;
+    for (index, desc) in ChainWalk::new(first).enumerate() {
+        let d = unsafe { &*desc };
+        let beat = 1usize << d.get_beat_size() as usize;
+        if d.get_next_desc_addr() == current.get_next_desc_addr() && d.get_src_addr() == current.get_src_addr()
+            && d.get_dst_addr() == current.get_dst_addr()
+        {
+            let remaining = current.get_block_transfer_count().min(d.get_block_transfer_count());
+            return TransferProgress {
+                descriptor_index: index,
+                beats_remaining: remaining,
+                bytes_done: bytes_done + (d.get_block_transfer_count() - remaining) as usize * beat,
+            };
+        }
+        bytes_done += d.get_block_transfer_count() as usize * beat;
+    }
+
+    TransferProgress {
+        descriptor_index: 0,
+        beats_remaining: unsafe { (*first).get_block_transfer_count() },
+        bytes_done: 0,
+    }
+}
+
+/// Iterator over the descriptors of the chain starting at `first`.
+/// 
+/// The walk ends at the last descriptor, or when the chain loops back on itself: either to the first descriptor, or 
+/// to any other descriptor, which is detected with Brent's algorithm. Every descriptor is visited, but some of the 
+/// descriptors in a loop that doesn't include the first descriptor can be visited twice before the loop is detected.
+struct ChainWalk {
+    first: *mut TransferDescriptor,
+    next: Option<*mut TransferDescriptor>,
+    started: bool,
+    checkpoint: *mut TransferDescriptor,
+    power: usize,
+    steps: usize,
+}
+
+impl ChainWalk {
+    fn new(first: *mut TransferDescriptor) -> ChainWalk {
+        ChainWalk {
+            first,
+            next: Some(first),
+            started: false,
+            checkpoint: first,
+            power: 1,
+            steps: 0,
+        }
+    }
+}
+
+impl Iterator for ChainWalk {
+    type Item = *mut TransferDescriptor;
+
+    fn next(&mut self) -> Option<*mut TransferDescriptor> {
+        let desc = self.next.take()?;
+        if self.started && (desc == self.first || desc == self.checkpoint) {
+            return None;
+        }
+        if self.steps == self.power {
+            self.checkpoint = desc;
+            self.power *= 2;
+            self.steps = 0;
+        }
+        self.started = true;
+        self.steps += 1;
+        self.next = unsafe { (*desc).get_next_desc_addr() };
+        Some(desc)
+    }
+}
+
+/// Update the statistics of the channel with the given ID with its handled interrupt flags and status.
+#[cfg(feature = "stats")]
+pub(crate) fn record_stats(id: u8, first: *mut TransferDescriptor, write_back: *mut TransferDescriptor,
+    flags: Interrupts, status: ChannelStatus)
+{
+    let enabled = channel_reg!(chctrla, id).read().enable().bit();
+    stats::record(id, flags, status, enabled, || {
+        chain_progress(first, unsafe { &ptr::read_volatile(write_back) }).bytes_done
+    });
 }
@@ -0,0 +1,90 @@
+use core::fmt;
+use crate::{TriggerSource, TriggerAction, Priority, Interrupts, EventConfig, TransferDescriptor, QosPolicy};
+#[cfg(feature = "samd5x")]
+use crate::{BurstLength, FifoThreshold};
+
+/// Error type for the kinds of errors that can occur while configuring a channel.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The channel is enabled, so its control registers can't be changed.
+    ChannelEnabled,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::ChannelEnabled => write!(w, "The channel is enabled, so it can't be configured."),
+        }
+    }
+}
+
+/// Configuration of a channel.
+/// 
+/// Use [`Channel::configure()`] to apply a configuration in a single call, and [`Channel::save_config()`] to capture 
+/// the configuration of a channel, such as before a low-power mode or when sharing a channel between peripherals.
+/// The default configuration matches the reset values of the channel registers.
+/// 
+/// [`Channel::configure()`]: struct.Channel.html#method.configure
+/// [`Channel::save_config()`]: struct.Channel.html#method.save_config
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ChannelConfig {
+    /// The trigger source of the channel.
+    pub source: TriggerSource,
+    /// The action started by a trigger.
+    pub trigger_action: TriggerAction,
+    /// The priority level of the channel.
+    pub priority: Priority,
+    /// The amount of beats in a burst.
+    #[cfg(feature = "samd5x")]
+    pub burst_length: BurstLength,
+    /// The amount of beats before destination writes occur.
+    #[cfg(feature = "samd5x")]
+    pub fifo_threshold: FifoThreshold,
+    /// Whether the channel continues to run in standby.
+    #[cfg(feature = "samd5x")]
+    pub run_standby: bool,
+    /// The enabled channel interrupts.
+    pub interrupts: Interrupts,
+    /// The event input and output settings.
+    pub events: EventConfig,
+    /// The first transfer descriptor of the channel.
+    pub descriptor: TransferDescriptor,
+}
+
+/// Configuration of the DMA controller.
+/// 
+/// Use [`DMAController::init()`] or [`DMAController::configure()`] to apply a configuration. Each register is written 
+/// once, so the settings take effect together. The default configuration matches the reset values of the controller 
+/// registers, where every priority level is disabled.
+/// 
+/// [`DMAController::init()`]: struct.DMAController.html#method.init
+/// [`DMAController::configure()`]: struct.DMAController.html#method.configure
+#[derive(Clone, Copy, Debug)]
+pub struct ControllerConfig {
+    /// Whether channels of each priority level take part in arbitration, indexed by level.
+    pub levels_enabled: [bool; 4],
+    /// Whether each priority level uses round-robin scheduling instead of static scheduling, indexed by level.
+    pub round_robin: [bool; 4],
+    /// The Quality of Service policy applied to every bus access of the DMA system, as with 
+    /// [`DMAController::set_qos()`]. The QoS of single priority levels or kinds of access can be changed once the 
+    /// configuration has been applied.
+    /// 
+    /// [`DMAController::set_qos()`]: struct.DMAController.html#method.set_qos
+    pub qos: QosPolicy,
+    /// Whether the DMA system keeps running while the CPU is halted by a debugger.
+    pub run_while_debug: bool,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> ControllerConfig {
+        ControllerConfig {
+            levels_enabled: [false; 4],
+            round_robin: [false; 4],
+            #[cfg(feature = "samd5x")]
+            qos: QosPolicy::LatencySensitive,
+            #[cfg(feature = "samd21")]
+            qos: QosPolicy::BandwidthSensitive,
+            run_while_debug: false,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use core::fmt;
+use core::sync::atomic::{self, Ordering};
+use crate::target_device::DAC;
+use crate::{Channel, TriggerSource, TransferDescriptor, BeatSize};
+
+/// DAC output used for waveform playback.
+pub enum DacOutput {
+    /// The first DAC output, fed on `Dac0Empty`.
+    Dac0,
+    /// The second DAC output, fed on `Dac1Empty`.
+    Dac1,
+}
+
+impl DacOutput {
+    fn empty_source(&self) -> TriggerSource {
+        match self {
+            DacOutput::Dac0 => TriggerSource::Dac0Empty,
+            DacOutput::Dac1 => TriggerSource::Dac1Empty,
+        }
+    }
+
+    fn data_addr(&self) -> *const () {
+        let dac = unsafe { &*DAC::ptr() };
+        match self {
+            DacOutput::Dac0 => &dac.data[0] as *const _ as *const (),
+            DacOutput::Dac1 => &dac.data[1] as *const _ as *const (),
+        }
+    }
+
+    fn databuf_addr(&self) -> *const () {
+        let dac = unsafe { &*DAC::ptr() };
+        match self {
+            DacOutput::Dac0 => &dac.databuf[0] as *const _ as *const (),
+            DacOutput::Dac1 => &dac.databuf[1] as *const _ as *const (),
+        }
+    }
+}
+
+/// Error type for the kinds of errors that can occur while controlling a [`WaveformPlayer`].
+///
+/// [`WaveformPlayer`]: struct.WaveformPlayer.html
+#[derive(Debug)]
+pub enum WaveformError {
+    /// A previously requested table swap has not taken effect yet.
+    SwapPending,
+}
+
+impl fmt::Display for WaveformError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WaveformError::SwapPending => write!(w, "A previously requested table swap has not taken effect yet."),
+        }
+    }
+}
+
+/// DAC waveform player.
+///
+/// The player streams a lookup table of samples to a DAC output, looping over the table forever using a
+/// self-linked descriptor. The table can be swapped while playing; the swap takes effect at a block boundary, so the
+/// previous table is always played to completion and no glitch is produced.
+///
+/// Swapping uses a second descriptor supplied by the caller. The new table is programmed into whichever descriptor
+/// is on standby, then the running descriptor is linked to it. Because the DMA engine follows the link held by its
+/// write-back copy, the current table may be played once more before the swap takes effect.
+pub struct WaveformPlayer {
+    channel: Channel,
+    spare: &'static mut TransferDescriptor,
+    data_reg: *const (),
+    table: &'static [u16],
+    next: Option<&'static [u16]>,
+    spare_active: bool,
+}
+
+impl WaveformPlayer {
+    /// Create a player that streams `table` to `output`, using `spare` for glitch-free table swaps.
+    ///
+    /// When `trigger` is `None`, a sample is written to the DAC data buffer each time it is empty, so the sample
+    /// rate is set by the event that starts DAC conversions. Otherwise, a sample is written straight to the DAC data
+    /// register on each `trigger`, such as `TriggerSource::Tc0Ovf` for a fixed sample rate set by a timer.
+    ///
+    /// The channel is configured, but not enabled until [`start`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` is empty or longer than 65535 samples.
+    ///
+    /// [`start`]: #method.start
+    pub fn new(mut channel: Channel, output: DacOutput, trigger: Option<TriggerSource>,
+        table: &'static [u16], spare: &'static mut TransferDescriptor) -> WaveformPlayer
+    {
+        channel.disable();
+        let data_reg = match trigger {
+            Some(source) => {
+                channel.set_beat_trigger(source);
+                output.data_addr()
+            },
+            None => {
+                channel.set_beat_trigger(output.empty_source());
+                output.databuf_addr()
+            }
+        };
+        program_descriptor(channel.get_first_descriptor(), data_reg, table);
+
+        WaveformPlayer {
+            channel,
+            spare,
+            data_reg,
+            table,
+            next: None,
+            spare_active: false,
+        }
+    }
+
+    /// Start playing the current table from its first sample.
+    pub fn start(&mut self) {
+        if let Some(next) = self.next.take() {
+            self.table = next;
+        }
+        program_descriptor(self.channel.get_first_descriptor(), self.data_reg, self.table);
+        self.spare_active = false;
+        self.channel.enable();
+    }
+
+    /// Stop playing. The DAC keeps its last sample.
+    pub fn stop(&mut self) {
+        self.channel.disable();
+    }
+
+    /// Return the table currently being played.
+    pub fn table(&mut self) -> &'static [u16] {
+        self.is_swap_pending();
+        self.table
+    }
+
+    /// Swap the table being played at the next block boundary.
+    ///
+    /// Returns `WaveformError::SwapPending` if a previous swap has not taken effect yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `table` is empty or longer than 65535 samples.
+    pub fn swap_table(&mut self, table: &'static [u16]) -> Result<(), WaveformError> {
+        if self.is_swap_pending() {
+            return Err(WaveformError::SwapPending);
+        }
+
+        let (active, standby) = self.descriptors();
+        unsafe {
+            program_descriptor(&mut *standby, self.data_reg, table);
+            // The standby descriptor must be complete in memory before the DMA engine can follow the link to it.
+            atomic::fence(Ordering::SeqCst);
+            (*active).set_next_desc_addr(standby);
+        }
+        self.next = Some(table);
+        Ok(())
+    }
+
+    /// Return `true` if a swapped table has not started playing yet.
+    pub fn is_swap_pending(&mut self) -> bool {
+        if let Some(next) = self.next {
+            let (_, standby) = self.descriptors();
+            let current = self.channel.read_writeback_descriptor();
+            if current.get_src_addr() == unsafe { (*standby).get_src_addr() } {
+                self.table = next;
+                self.next = None;
+                self.spare_active = !self.spare_active;
+            }
+        }
+        self.next.is_some()
+    }
+
+    /// Stop the player, returning the channel and the spare descriptor.
+    pub fn free(mut self) -> (Channel, &'static mut TransferDescriptor) {
+        self.channel.disable();
+        (self.channel, self.spare)
+    }
+
+    /// Get the active and standby descriptors, in that order.
+    fn descriptors(&mut self) -> (*mut TransferDescriptor, *mut TransferDescriptor) {
+        let first = self.channel.get_first_descriptor() as *mut TransferDescriptor;
+        let spare = &mut *self.spare as *mut TransferDescriptor;
+        if self.spare_active {
+            (spare, first)
+        } else {
+            (first, spare)
+        }
+    }
+}
+
+/// Program a self-linked descriptor that streams `table` to the register at `data_reg`.
+fn program_descriptor(desc: &mut TransferDescriptor, data_reg: *const (), table: &'static [u16]) {
+    assert!(!table.is_empty() && table.len() <= u16::MAX as usize);
+
+    let desc_addr = desc as *mut _;
+    *desc = Default::default();
+    desc.set_beat_size(BeatSize::HWord);
+    desc.set_block_count(table.len() as u16);
+    desc.set_src_addr_increment(true);
+    desc.set_src_start_addr(table.as_ptr() as *const ());
+    desc.set_dst_addr(data_reg);
+    desc.set_next_desc_addr(desc_addr);
+    desc.set_valid();
+}
@@ -0,0 +1,82 @@
+use embedded_hal::blocking::delay::DelayUs;
+
+/// A deadline for a blocking wait.
+/// 
+/// Any `FnMut() -> bool` closure is a deadline, which has expired once the closure returns `true`.
+pub trait Deadline {
+    /// Return `true` once the deadline has expired. This is called repeatedly while waiting.
+    fn expired(&mut self) -> bool;
+}
+
+impl<F: FnMut() -> bool> Deadline for F {
+    fn expired(&mut self) -> bool {
+        self()
+    }
+}
+
+/// A deadline measured with an `embedded-hal` delay.
+/// 
+/// Each time the deadline is checked, the delay blocks for the poll interval, until the whole timeout has elapsed.
+pub struct DelayDeadline<D: DelayUs<u32>> {
+    delay: D,
+    remaining_us: u32,
+    interval_us: u32,
+}
+
+impl<D: DelayUs<u32>> DelayDeadline<D> {
+    /// Create a deadline expiring after `timeout_us` microseconds, checked every `interval_us` microseconds.
+    pub fn new(delay: D, timeout_us: u32, interval_us: u32) -> DelayDeadline<D> {
+        DelayDeadline {
+            delay,
+            remaining_us: timeout_us,
+            interval_us: interval_us.max(1),
+        }
+    }
+
+    /// Return the delay.
+    pub fn free(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayUs<u32>> Deadline for DelayDeadline<D> {
+    fn expired(&mut self) -> bool {
+        if self.remaining_us == 0 {
+            return true;
+        }
+        let step = self.interval_us.min(self.remaining_us);
+        self.delay.delay_us(step);
+        self.remaining_us -= step;
+        false
+    }
+}
+
+/// A deadline measured with a free-running tick counter.
+/// 
+/// The counter may wrap around, as long as the deadline is less than half the range of the counter away.
+pub struct TickDeadline<F: FnMut() -> u32> {
+    now: F,
+    deadline: u32,
+}
+
+impl<F: FnMut() -> u32> TickDeadline<F> {
+    /// Create a deadline expiring once `now()` reaches the `deadline` tick.
+    pub fn new(now: F, deadline: u32) -> TickDeadline<F> {
+        TickDeadline {
+            now,
+            deadline,
+        }
+    }
+
+    /// Create a deadline expiring `ticks` ticks after the current value of `now()`.
+    pub fn after(mut now: F, ticks: u32) -> TickDeadline<F> {
+        let deadline = now().wrapping_add(ticks);
+        TickDeadline::new(now, deadline)
+    }
+}
+
+impl<F: FnMut() -> u32> Deadline for TickDeadline<F> {
+    fn expired(&mut self) -> bool {
+        (self.now)().wrapping_sub(self.deadline) as i32 >= 0
+    }
+}
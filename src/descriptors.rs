@@ -1,4 +1,5 @@
 use core::u16;
+use core::ptr;
 use crate::{
     BeatSize,
     StepSize,
@@ -82,6 +83,95 @@ impl TransferDescriptor {
         };
     }
 
+    /// Set the address of the next linked descriptor. A null pointer ends the descriptor chain.
+    /// 
+    /// Unlike [`link_descriptor()`], this can link a descriptor to itself to form a circular transfer.
+    /// 
+    /// [`link_descriptor()`]: #method.link_descriptor
+    pub fn set_next_desc_addr(&mut self, addr: *mut TransferDescriptor) {
+        self.descaddr = if addr.is_null() {
+            None
+        } else {
+            Some(addr)
+        };
+    }
+
+    /// Link a write-back descriptor past its upcoming descriptor, so the block it describes is skipped. Returns 
+    /// `false` if there is no upcoming descriptor.
+    pub(crate) fn link_past_next(&mut self) -> bool {
+        match self.get_next_desc_addr() {
+            Some(next) => {
+                let after = unsafe { (*next).get_next_desc_addr() };
+                self.set_next_desc_addr(after.unwrap_or(ptr::null_mut()));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Make the block raise the transfer complete interrupt when it ends, keeping its suspend action if it has one.
+    pub(crate) fn enable_block_interrupt(&mut self) {
+        self.btctrl.insert(RawBlockTransferCtrl::BLOCKACT_0);
+    }
+
+    /// Set the source address from the address of the first beat of the block.
+    /// 
+    /// When the source address is incremented, the DMA system expects the address just past the last beat of the 
+    /// block. This computes that address from the block count, beat size and step settings, so those must be 
+    /// configured before calling this method.
+    /// 
+    /// ```
+    /// use samd_dma::{BeatSize, StepSize, TransferDescriptor};
+    /// 
+    /// let mut desc = TransferDescriptor::new();
+    /// desc.set_beat_size(BeatSize::HWord);
+    /// desc.set_block_count(10);
+    /// desc.set_src_addr_increment(true);
+    /// desc.set_dest_addr_increment(true);
+    /// desc.set_step_selection(true);
+    /// desc.set_step_size(StepSize::X4);
+    /// desc.set_src_start_addr(0x2000_0000 as *const ());
+    /// desc.set_dst_start_addr(0x2000_1000 as *const ());
+    /// 
+    /// // SRCADDR = SRCADDR_START + BTCNT * (BEATSIZE + 1) * 2^STEPSIZE = 0x2000_0000 + 10 * 2 * 4
+    /// assert_eq!(desc.get_src_addr(), Some(0x2000_0050 as *const ()));
+    /// // DSTADDR = DSTADDR_START + BTCNT * (BEATSIZE + 1) = 0x2000_1000 + 10 * 2
+    /// assert_eq!(desc.get_dst_addr(), Some(0x2000_1014 as *const ()));
+    /// ```
+    pub fn set_src_start_addr(&mut self, addr: *const ()) {
+        let offset = if self.get_src_addr_increment() {
+            self.block_span(self.get_step_selection())
+        } else {
+            0
+        };
+        self.set_src_addr((addr as *const u8).wrapping_add(offset) as *const ());
+    }
+
+    /// Set the destination address from the address of the first beat of the block.
+    /// 
+    /// When the destination address is incremented, the DMA system expects the address just past the last beat of 
+    /// the block. This computes that address from the block count, beat size and step settings, so those must be 
+    /// configured before calling this method.
+    pub fn set_dst_start_addr(&mut self, addr: *const ()) {
+        let offset = if self.get_dest_addr_increment() {
+            self.block_span(!self.get_step_selection())
+        } else {
+            0
+        };
+        self.set_dst_addr((addr as *const u8).wrapping_add(offset) as *const ());
+    }
+
+    /// The number of bytes an incremented address advances over the whole block.
+    fn block_span(&self, stepped: bool) -> usize {
+        let beat = 1usize << self.get_beat_size() as usize;
+        let step = if stepped {
+            1usize << self.get_step_size() as usize
+        } else {
+            1
+        };
+        self.btcnt as usize * beat * step
+    }
+
     /// Mark the descriptor as valid.
     pub fn set_valid(&mut self) {
         self.btctrl.insert(RawBlockTransferCtrl::VALID);
@@ -0,0 +1,103 @@
+use crate::{Interrupts, Status};
+
+/// Mask of the ID field of the INTPEND register.
+#[cfg(feature = "samd5x")]
+const ID_MASK: u16 = 0x001f;
+/// Mask of the ID field of the INTPEND register.
+#[cfg(feature = "samd21")]
+const ID_MASK: u16 = 0x000f;
+const FLAGS_SHIFT: u16 = 8;
+#[cfg(feature = "samd5x")]
+const CRCERR: u16 = 1 << 12;
+const FERR: u16 = 1 << 13;
+const BUSY: u16 = 1 << 14;
+const PEND: u16 = 1 << 15;
+
+/// The decoded INTPEND register, holding the interrupt flags and status of a single channel.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct InterruptPending {
+    /// The ID of the channel.
+    pub id: u8,
+    /// The interrupt flags of the channel.
+    pub flags: Interrupts,
+    /// A transfer is pending on the channel.
+    pub pend: bool,
+    /// The channel has started a transfer.
+    pub busy: bool,
+    /// An invalid descriptor was fetched by the channel.
+    pub ferr: bool,
+    /// The CRC module detected data corruption on the channel.
+    #[cfg(feature = "samd5x")]
+    pub crcerr: bool,
+}
+
+impl InterruptPending {
+    /// Decode a value read from the INTPEND register.
+    ///
+    /// The channel ID is held in the low bits, followed by the `TERR`, `TCMPL` and `SUSP` flags in bits 8 to 10, then
+    /// the `CRCERR` (SAMD51 only), `FERR`, `BUSY` and `PEND` status bits in bits 12 to 15.
+    ///
+    /// ```
+    /// use samd_dma::{InterruptPending, Interrupts};
+    ///
+    /// let pending = InterruptPending::from_bits(0x8205);
+    /// assert_eq!(pending.id, 5);
+    /// assert_eq!(pending.flags, Interrupts::TCMPL);
+    /// assert!(pending.pend && !pending.busy && !pending.ferr);
+    ///
+    /// let pending = InterruptPending::from_bits(0x6503);
+    /// assert_eq!(pending.id, 3);
+    /// assert_eq!(pending.flags, Interrupts::TERR | Interrupts::SUSP);
+    /// assert!(!pending.pend && pending.busy && pending.ferr);
+    ///
+    /// assert_eq!(InterruptPending::from_bits(0x0700).flags, Interrupts::all());
+    /// ```
+    pub fn from_bits(bits: u16) -> InterruptPending {
+        InterruptPending {
+            id: (bits & ID_MASK) as u8,
+            flags: Interrupts::from_bits_truncate((bits >> FLAGS_SHIFT) as u8),
+            pend: bits & PEND != 0,
+            busy: bits & BUSY != 0,
+            ferr: bits & FERR != 0,
+            #[cfg(feature = "samd5x")]
+            crcerr: bits & CRCERR != 0,
+        }
+    }
+
+    /// Get the value to write to the INTPEND register to clear the interrupt flags of this channel. Writing it also
+    /// selects the channel.
+    ///
+    /// ```
+    /// use samd_dma::InterruptPending;
+    ///
+    /// let pending = InterruptPending::from_bits(0xc603);
+    /// assert_eq!(pending.clear_bits(), 0x0603);
+    /// assert_eq!(InterruptPending::select_bits(7), 0x0007);
+    /// ```
+    pub fn clear_bits(&self) -> u16 {
+        InterruptPending::select_bits(self.id) | (self.flags.bits() as u16) << FLAGS_SHIFT
+    }
+
+    /// Get the value to write to the INTPEND register to select a channel, without clearing any flag.
+    pub fn select_bits(id: u8) -> u16 {
+        id as u16 & ID_MASK
+    }
+
+    /// Get the status of the channel, with pending taking precedence over busy, then errors.
+    pub fn status(&self) -> Option<Status> {
+        if self.pend {
+            return Some(Status::Pending);
+        }
+        if self.busy {
+            return Some(Status::Busy);
+        }
+        if self.ferr {
+            return Some(Status::FetchError);
+        }
+        #[cfg(feature = "samd5x")]
+        if self.crcerr {
+            return Some(Status::CRCError);
+        }
+        None
+    }
+}
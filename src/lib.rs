@@ -26,6 +26,7 @@ compile_error!("Please use this crate's feature flags to select a SAM micro-cont
 #[macro_use]
 extern crate bitflags;
 extern crate smart_default;
+extern crate embedded_hal;
 
 #[cfg(feature = "samd51j")]
 use atsamd51j as target_device;
@@ -45,7 +46,27 @@ use atsamd21j as target_device;
 mod channel;
 mod types;
 mod descriptors;
+mod config;
+mod deadline;
+mod arbitration;
+mod snapshot;
+mod intpend;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "samd5x")]
+mod uart;
+#[cfg(feature = "samd5x")]
+mod adc;
+#[cfg(feature = "samd5x")]
+mod dac;
+#[cfg(feature = "samd5x")]
+mod pwm;
+mod blit;
+mod swap;
+mod shared;
+mod queue;
 pub mod storage;
+pub mod model;
 pub mod consts {
     //! Contains types used to identify DMA channels.
     #![allow(missing_docs)]
@@ -96,6 +117,7 @@ pub mod consts {
 
 #[allow(unused_imports)]
 use core::u32;
+use core::fmt;
 use core::u16;
 use target_device::DMAC;
 use typenum::consts::*;
@@ -105,6 +127,58 @@ use storage::DmaStorage;
 pub use self::channel::*;
 pub use self::types::*;
 pub use self::descriptors::*;
+pub use self::config::*;
+pub use self::deadline::*;
+pub use self::arbitration::*;
+pub use self::snapshot::*;
+pub use self::intpend::*;
+#[cfg(feature = "stats")]
+pub use self::stats::ChannelStats;
+#[cfg(feature = "samd5x")]
+pub use self::uart::*;
+#[cfg(feature = "samd5x")]
+pub use self::adc::*;
+#[cfg(feature = "samd5x")]
+pub use self::dac::*;
+#[cfg(feature = "samd5x")]
+pub use self::pwm::*;
+pub use self::blit::*;
+pub use self::swap::*;
+pub use self::shared::*;
+pub use self::queue::*;
+
+/// Error type for the kinds of errors that can occur while tearing down a [`DMAController`].
+/// 
+/// [`DMAController`]: struct.DMAController.html
+pub enum FreeError<T: 'static + DmaStorage> {
+    /// Some channels have not been returned to the controller, which is handed back unchanged.
+    ChannelsTaken {
+        /// The controller.
+        controller: DMAController<T>,
+        /// The channels that have not been returned.
+        channels: Channels,
+    },
+}
+
+impl<T: 'static + DmaStorage> fmt::Debug for FreeError<T> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FreeError::ChannelsTaken { channels, .. } => {
+                w.debug_struct("ChannelsTaken").field("channels", channels).finish()
+            },
+        }
+    }
+}
+
+impl<T: 'static + DmaStorage> fmt::Display for FreeError<T> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FreeError::ChannelsTaken { channels, .. } => {
+                write!(w, "Channels {:#x} have not been returned.", channels.bits())
+            },
+        }
+    }
+}
 
 /// DMA system controller.
 /// 
@@ -118,19 +192,107 @@ pub struct DMAController<T: 'static + DmaStorage> {
     dmac: DMAC,
 }
 
+/// The peripheral controlling the bus clocks of the DMA system.
+#[cfg(feature = "samd5x")]
+pub type ClockController = target_device::MCLK;
+
+/// The peripheral controlling the bus clocks of the DMA system.
+#[cfg(feature = "samd21")]
+pub type ClockController = target_device::PM;
+
 impl<T: 'static + DmaStorage> DMAController<T> {
-    /// Initialise the DMA Controller with the specified storage.
-    pub fn init(dmac: DMAC, storage: &'static mut T) -> DMAController<T> {
+    /// Initialise the DMA Controller with the specified storage and configuration.
+    /// 
+    /// If `clocks` is provided, the bus clocks of the DMA system are enabled first. The controller is then disabled, 
+    /// waiting for any ongoing burst transfer to complete, and reset, before the storage addresses are written and 
+    /// `config` is applied. The DMA system is left disabled until [`enable`] is called.
+    /// 
+    /// [`enable`]: #method.enable
+    pub fn init(dmac: DMAC, storage: &'static mut T, clocks: Option<&mut ClockController>,
+        config: &ControllerConfig) -> DMAController<T>
+    {
+        if let Some(clocks) = clocks {
+            clocks.ahbmask.modify(|_, w| w.dmac_().set_bit());
+            #[cfg(feature = "samd21")]
+            clocks.apbbmask.modify(|_, w| w.dmac_().set_bit());
+        }
+
+        reset(&dmac);
         dmac.baseaddr.write(|w| unsafe { w.bits(storage.baseaddr() as u32) });
         dmac.wrbaddr.write(|w| unsafe { w.bits(storage.wbaddr() as u32) });
-        DMAController {
+        let mut controller = DMAController {
             #[cfg(feature = "samd21")]
             channels: u16::MAX >> 16 - T::Size::U16,
             #[cfg(feature = "samd5x")]
             channels: u32::MAX >> 32 - T::Size::U32,
             storage,
             dmac
+        };
+        controller.configure(config);
+        controller
+    }
+
+    /// Apply a controller configuration. The DMA system is left enabled or disabled as it was.
+    pub fn configure(&mut self, config: &ControllerConfig) {
+        let levels = config.levels_enabled;
+        self.dmac.ctrl.modify(|_, w| {
+            w.lvlen0().bit(levels[0])
+                .lvlen1().bit(levels[1])
+                .lvlen2().bit(levels[2])
+                .lvlen3().bit(levels[3])
+        });
+
+        let rr = config.round_robin;
+        let qos = config.qos as u8;
+        #[cfg(feature = "samd5x")]
+        self.dmac.prictrl0.modify(|_, w| {
+            w.rrlvlen0().bit(rr[0])
+                .rrlvlen1().bit(rr[1])
+                .rrlvlen2().bit(rr[2])
+                .rrlvlen3().bit(rr[3])
+                .qos0().bits(qos)
+                .qos1().bits(qos)
+                .qos2().bits(qos)
+                .qos3().bits(qos)
+        });
+        #[cfg(feature = "samd21")]
+        {
+            self.dmac.prictrl0.modify(|_, w| {
+                w.rrlvlen0().bit(rr[0])
+                    .rrlvlen1().bit(rr[1])
+                    .rrlvlen2().bit(rr[2])
+                    .rrlvlen3().bit(rr[3])
+            });
+            self.dmac.qosctrl.write(|w| w.dqos().bits(qos).fqos().bits(qos).wrbqos().bits(qos));
+        }
+
+        self.dmac.dbgctrl.write(|w| w.dbgrun().bit(config.run_while_debug));
+    }
+
+    /// Tear down the controller, returning the DMAC peripheral and the storage.
+    /// 
+    /// The DMA system is disabled, waiting for any ongoing burst transfer to complete, and reset, so the peripheral 
+    /// can be initialised again in a clean state. Returns `FreeError::ChannelsTaken` with the controller if any 
+    /// channel has not been returned.
+    pub fn free(self) -> Result<(DMAC, &'static mut T), FreeError<T>> {
+        let taken = self.taken_channels();
+        if !taken.is_empty() {
+            return Err(FreeError::ChannelsTaken {
+                controller: self,
+                channels: taken,
+            });
         }
+        reset(&self.dmac);
+        Ok((self.dmac, self.storage))
+    }
+
+    /// Get a bitfield of the channels that have been taken and not returned.
+    pub fn taken_channels(&self) -> Channels {
+        #[cfg(feature = "samd21")]
+        let taken = ((u16::MAX >> (16 - T::Size::U16)) & !self.channels).into();
+        #[cfg(feature = "samd5x")]
+        let taken = (u32::MAX >> (32 - T::Size::U32)) & !self.channels;
+        Channels::from_bits_truncate(taken)
     }
 
     /// Disable all channels and the CRC module. This will abort any ongoing DMA transactions.
@@ -179,7 +341,7 @@ impl<T: 'static + DmaStorage> DMAController<T> {
         if self.channels & (1 << U::USIZE) == 0 {
             None
         } else {
-            self.channels |= 1 << U::USIZE;
+            self.channels &= !(1 << U::USIZE);
             unsafe {
                 Some(Channel::new(U::U8,
                     self.storage.baseaddr().offset(U::to_isize()) as *mut TransferDescriptor,
@@ -197,7 +359,7 @@ impl<T: 'static + DmaStorage> DMAController<T> {
     pub fn return_channel(&mut self, mut channel: Channel) {
         channel.disable();
         channel.reset();
-        self.channels &= !(1 << channel.id());
+        self.channels |= 1 << channel.id();
     }
 
     /// Allow channels with the corresponding priority level to be part of arbitration.
@@ -219,7 +381,67 @@ impl<T: 'static + DmaStorage> DMAController<T> {
         })
     }
 
+    /// Apply an arbitration scheme.
+    /// 
+    /// The priority levels and their scheduling are written once each, then the priority level of every channel 
+    /// assigned to a level is set. Channels that are not assigned to any level keep their priority level.
+    /// 
+    /// # Safety
+    /// 
+    /// The priority level of a channel is set without taking it, so the interrupt safety issues of 
+    /// `Channel::set_priority` on SAMD21 apply to every channel that has been taken.
+    pub fn apply_arbitration(&mut self, arbitration: &Arbitration) {
+        let enabled = |level| arbitration.get_scheduling(level).is_some();
+        let rr = |level| arbitration.get_scheduling(level) == Some(Scheduling::RoundRobin);
+        self.dmac.ctrl.modify(|_, w| {
+            w.lvlen0().bit(enabled(Priority::Level0))
+                .lvlen1().bit(enabled(Priority::Level1))
+                .lvlen2().bit(enabled(Priority::Level2))
+                .lvlen3().bit(enabled(Priority::Level3))
+        });
+        self.dmac.prictrl0.modify(|_, w| {
+            w.rrlvlen0().bit(rr(Priority::Level0))
+                .rrlvlen1().bit(rr(Priority::Level1))
+                .rrlvlen2().bit(rr(Priority::Level2))
+                .rrlvlen3().bit(rr(Priority::Level3))
+        });
+
+        for &level in &[Priority::Level0, Priority::Level1, Priority::Level2, Priority::Level3] {
+            let channels = arbitration.get_channels(level).bits();
+            for id in (0..T::Size::U8).filter(|id| channels & 1 << id != 0) {
+                channel::write_priority(id, level);
+            }
+        }
+    }
+
+    /// Read back the arbitration scheme in use, with every channel of the storage assigned to its priority level.
+    pub fn arbitration(&self) -> Arbitration {
+        let ctrl = self.dmac.ctrl.read();
+        let prictrl = self.dmac.prictrl0.read();
+        let levels = [
+            (Priority::Level0, ctrl.lvlen0().bit(), prictrl.rrlvlen0().bit()),
+            (Priority::Level1, ctrl.lvlen1().bit(), prictrl.rrlvlen1().bit()),
+            (Priority::Level2, ctrl.lvlen2().bit(), prictrl.rrlvlen2().bit()),
+            (Priority::Level3, ctrl.lvlen3().bit(), prictrl.rrlvlen3().bit()),
+        ];
+
+        let mut arbitration = Arbitration::new();
+        for &(level, enabled, rr) in &levels {
+            if enabled {
+                let scheduling = if rr { Scheduling::RoundRobin } else { Scheduling::Static };
+                arbitration = arbitration.level(level, scheduling);
+            }
+        }
+        for id in 0..T::Size::U8 {
+            arbitration = arbitration.channel(id, channel::read_priority(id));
+        }
+        arbitration
+    }
+
     /// Get the Quality of Service guarantee for the specified priority level.
+    /// 
+    /// The SAMD51 applies a single QoS to the data transfers and descriptor accesses of a priority level, so there 
+    /// is no separate data transfer and fetch QoS like on SAMD21.
     #[cfg(feature = "samd5x")]
     pub fn get_priority_qos(&self, level: Priority) -> QoS {
         match level {
@@ -241,6 +463,45 @@ impl<T: 'static + DmaStorage> DMAController<T> {
         }
     }
 
+    /// Apply a Quality of Service policy to every bus access of the DMA system.
+    /// 
+    /// On SAMD21, this sets the data transfer, descriptor fetch and write-back QoS. On SAMD51, this sets the QoS of 
+    /// every priority level. See [`QosPolicy`] for how policies map onto each family.
+    /// 
+    /// [`QosPolicy`]: enum.QosPolicy.html
+    pub fn set_qos(&mut self, policy: QosPolicy) {
+        let value = policy as u8;
+        #[cfg(feature = "samd5x")]
+        self.dmac.prictrl0.modify(|_, w| {
+            w.qos0().bits(value)
+                .qos1().bits(value)
+                .qos2().bits(value)
+                .qos3().bits(value)
+        });
+        #[cfg(feature = "samd21")]
+        self.dmac.qosctrl.write(|w| w.dqos().bits(value).fqos().bits(value).wrbqos().bits(value));
+    }
+
+    /// Get the Quality of Service policy applied to every bus access of the DMA system, or `None` if different 
+    /// policies are in use.
+    pub fn get_qos(&self) -> Option<QosPolicy> {
+        #[cfg(feature = "samd5x")]
+        let (first, rest) = {
+            let reg = self.dmac.prictrl0.read();
+            (reg.qos0().bits(), [reg.qos1().bits(), reg.qos2().bits(), reg.qos3().bits()])
+        };
+        #[cfg(feature = "samd21")]
+        let (first, rest) = {
+            let reg = self.dmac.qosctrl.read();
+            (reg.dqos().bits(), [reg.fqos().bits(), reg.wrbqos().bits()])
+        };
+        if rest.iter().all(|&value| value == first) {
+            Some(QosPolicy::from(first))
+        } else {
+            None
+        }
+    }
+
     /// Get the Quality of Service guarantee for data transfer.
     #[cfg(feature = "samd21")]
     pub fn get_data_transfer_qos(&self) -> QoS {
@@ -318,10 +579,77 @@ impl<T: 'static + DmaStorage> DMAController<T> {
         self.dmac.active.read().id().bits()
     }
 
+    /// Get the statistics counters of the channel with the given ID.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the ID is not a channel of the storage.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self, id: u8) -> ChannelStats {
+        assert!(id < T::Size::U8);
+        stats::get(id)
+    }
+
+    /// Reset the statistics counters of the channel with the given ID.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the ID is not a channel of the storage.
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self, id: u8) {
+        assert!(id < T::Size::U8);
+        stats::reset(id);
+    }
+
+    /// Set the clock used to measure the latency from a software trigger to the completion of a transaction, such as 
+    /// a function reading a free-running timer. The clock may wrap around.
+    #[cfg(feature = "stats")]
+    pub fn set_stats_clock(&mut self, clock: fn() -> u32) {
+        stats::set_clock(clock);
+    }
+
+    /// Get the first and write-back descriptors of the channel with the given ID.
+    #[cfg(feature = "stats")]
+    fn descriptors(&self, id: u8) -> (*mut TransferDescriptor, *mut TransferDescriptor) {
+        unsafe {
+            (self.storage.baseaddr().offset(id as isize) as *mut TransferDescriptor,
+                self.storage.wbaddr().offset(id as isize) as *mut TransferDescriptor)
+        }
+    }
+
+    /// Capture the state of the DMA system and of every channel of the storage in one structure, for diagnostics.
+    /// 
+    /// The registers are read in an interrupt-free section, so the snapshot is not torn by interrupt handlers.
+    pub fn snapshot(&self) -> DmacSnapshot {
+        cortex_m::interrupt::free(|_| {
+            let active = self.dmac.active.read();
+            let mut snapshot = DmacSnapshot {
+                interrupt_status: self.get_channel_interrupt_status(),
+                pending: self.get_pending_channels(),
+                busy: self.get_busy_channels(),
+                active: ActiveStatus {
+                    level_requests: [active.lvlex0().bit(), active.lvlex1().bit(), active.lvlex2().bit(),
+                        active.lvlex3().bit()],
+                    id: active.id().bits(),
+                    busy: active.abusy().bit(),
+                    btcnt: active.btcnt().bits(),
+                },
+                channels: [ChannelSnapshot::default(); MAX_CHANNELS],
+                channel_count: T::Size::USIZE,
+            };
+            for id in 0..T::Size::U8 {
+                snapshot.channels[id as usize] = channel::read_snapshot(id);
+            }
+            snapshot
+        })
+    }
+
     /// Send a trigger request to a channel.
     /// 
     /// If the channel is not in a pending state, the request is ignored.
     pub fn trigger_channel(&mut self, id: u8) {
+        #[cfg(feature = "stats")]
+        stats::record_trigger(id);
         self.dmac.swtrigctrl.modify(|r, w| unsafe { w.bits(r.bits() | 1 << id) })
     }
 
@@ -345,82 +673,83 @@ impl<T: 'static + DmaStorage> DMAController<T> {
         }
     }
 
+    /// Read the INTPEND register, holding the interrupt flags and status of the lowest channel with pending 
+    /// interrupts, if there is one.
+    pub fn get_lowest_interrupt_pending(&self) -> Option<InterruptPending> {
+        let pending = InterruptPending::from_bits(self.dmac.intpend.read().bits());
+        if pending.flags.is_empty() {
+            None
+        } else {
+            Some(pending)
+        }
+    }
+
+    /// Select a channel in the INTPEND register and read its interrupt flags and status, without clearing them.
+    pub fn read_interrupt_pending(&mut self, id: u8) -> InterruptPending {
+        self.dmac.intpend.write(|w| unsafe { w.bits(InterruptPending::select_bits(id)) });
+        InterruptPending::from_bits(self.dmac.intpend.read().bits())
+    }
+
+    /// Clear the interrupt flags of `pending` through the INTPEND register.
+    pub fn clear_interrupt_pending(&mut self, pending: &InterruptPending) {
+        #[cfg(feature = "stats")]
+        {
+            // Only count the flags that are still set, so flags already cleared elsewhere aren't counted again.
+            let flags = self.read_interrupt_pending(pending.id).flags & pending.flags;
+            let mut status = ChannelStatus::empty();
+            status.set(ChannelStatus::PEND, pending.pend);
+            status.set(ChannelStatus::BUSY, pending.busy);
+            status.set(ChannelStatus::FERR, pending.ferr);
+            #[cfg(feature = "samd5x")]
+            status.set(ChannelStatus::CRCERR, pending.crcerr);
+            let (first, write_back) = self.descriptors(pending.id);
+            channel::record_stats(pending.id, first, write_back, flags, status);
+        }
+        self.dmac.intpend.write(|w| unsafe { w.bits(pending.clear_bits()) });
+    }
+
     /// Get the lowest pending interrupt channel's interrupt flags, if present.
     /// 
     /// The ID and interrupt flags are returned.
     pub fn get_lowest_pending_channel_interrupts(&self) -> Option<(u8, Interrupts)> {
-        let reg = self.dmac.intpend.read();
-        let int = Interrupts::from_bits_truncate((reg.bits() & 0x700).to_be_bytes()[0]);
-        if !int.is_empty() {
-            Some((reg.id().bits(), int))
-        } else {
-            None
-        }
+        self.get_lowest_interrupt_pending().map(|pending| (pending.id, pending.flags))
     }
 
     /// Get the lowest pending interrupt channel's status, if present.
     /// 
     /// The ID and status are returned.
     pub fn get_lowest_pending_channel_status(&self) -> Option<(u8, Option<Status>)> {
-        let reg = self.dmac.intpend.read();
-        let int = Interrupts::from_bits_truncate((reg.bits() & 0x700).to_be_bytes()[0]);
-        if !int.is_empty() {
-            if reg.pend().bit_is_set() {
-                return Some((reg.id().bits(), Some(Status::Pending)));
-            }
-    
-            if reg.busy().bit_is_set() {
-                return Some((reg.id().bits(), Some(Status::Busy)));
-            }
-    
-            if reg.ferr().bit_is_set() {
-                return Some((reg.id().bits(), Some(Status::FetchError)));
-            }
-    
-            #[cfg(feature = "samd5x")]
-            if reg.crcerr().bit_is_set() {
-                return Some((reg.id().bits(), Some(Status::CRCError)));
-            }
-
-            return Some((reg.id().bits(), None));
-        } else {
-            None
-        }
+        self.get_lowest_interrupt_pending().map(|pending| (pending.id, pending.status()))
     }
 
     /// Get the interrupt flags of a particular channel.
     pub fn get_channel_pending_interrupts(&mut self, id: u8) -> Interrupts {
-        self.dmac.intpend.modify(|_, w| unsafe { w.id().bits(id) });
-        Interrupts::from_bits_truncate((self.dmac.intpend.read().bits() & 0x700).to_be_bytes()[0])
+        self.read_interrupt_pending(id).flags
     }
 
-    /// Set the interrupt flags of a particular channel.
+    /// Clear the interrupt flags of a particular channel. Only the flags set in `int` are cleared.
     pub fn set_channel_pending_interrupts(&mut self, id: u8, int: Interrupts) {
-        self.dmac.intpend.write(|w| unsafe { w.bits(u16::from_be_bytes([int.bits(), id])) })
+        self.dmac.intpend.write(|w| unsafe { w.bits(InterruptPending::select_bits(id) | (int.bits() as u16) << 8) })
     }
 
     /// Get the status of a particular channel.
     pub fn get_channel_status(&mut self, id: u8) -> Option<Status> {
-        self.dmac.intpend.modify(|_, w| unsafe { w.id().bits(id) });
-        let reg = self.dmac.intpend.read();
-
-        if reg.pend().bit_is_set() {
-            return Some(Status::Pending);
-        }
-
-        if reg.busy().bit_is_set() {
-            return Some(Status::Busy);
-        }
-
-        if reg.ferr().bit_is_set() {
-            return Some(Status::FetchError);
-        }
-
-        #[cfg(feature = "samd5x")]
-        if reg.crcerr().bit_is_set() {
-            return Some(Status::CRCError);
-        }
+        self.read_interrupt_pending(id).status()
+    }
+}
 
-        None
+/// Disable the DMA system and the CRC module, waiting for any ongoing burst transfer to complete, then reset it.
+fn reset(dmac: &DMAC) {
+    #[cfg(feature = "samd5x")]
+    {
+        dmac.ctrl.modify(|_, w| w.dmaenable().clear_bit());
+        while dmac.ctrl.read().dmaenable().bit_is_set() {}
     }
-}
\ No newline at end of file
+    #[cfg(feature = "samd21")]
+    {
+        dmac.ctrl.modify(|_, w| w.dmaenable().clear_bit().crcenable().clear_bit());
+        while dmac.ctrl.read().dmaenable().bit_is_set() || dmac.ctrl.read().crcenable().bit_is_set() {}
+    }
+    dmac.ctrl.write(|w| w.swrst().set_bit());
+    while dmac.ctrl.read().swrst().bit_is_set() {}
+}
@@ -0,0 +1,304 @@
+//! Software model of the DMA descriptor engine.
+//!
+//! The model executes descriptor chains on the CPU, moving memory beat by beat the way the DMA system would. This
+//! allows descriptor chains to be validated on a host, without any hardware. Only the descriptor engine and the
+//! interrupt flags of the channel are modelled; triggers, arbitration and the other channel registers are not.
+//!
+//! # Safety
+//!
+//! Running a descriptor chain reads and writes memory through the raw addresses stored in the descriptors, exactly
+//! like the DMA system does. Every address the chain touches must be valid for the duration of the run.
+use core::ptr;
+use crate::{TransferDescriptor, BlockAction, Interrupts, ChannelStatus, TransactionError, WaitResult};
+use crate::channel::transaction_status;
+
+/// The reason the model stopped running a descriptor chain.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The last block of the chain completed, so the channel would be disabled.
+    Done,
+    /// A block with a suspend block action completed, so the channel would be suspended.
+    Suspended,
+    /// An invalid descriptor was fetched, so the channel would be suspended with a fetch error.
+    InvalidDescriptor,
+    /// The block limit was reached before the chain ended, such as when running a circular chain.
+    BlockLimit,
+}
+
+/// A model of a single DMA channel running a descriptor chain.
+///
+/// Descriptors are read the way the DMA system reads them, so an incremented address is the address just past the
+/// last beat of the block, with the step size applied to the address selected by STEPSEL.
+///
+/// ```
+/// use samd_dma::{BeatSize, StepSize, TransferDescriptor};
+/// use samd_dma::model::{Model, Outcome};
+///
+/// let mut src = [0u16; 12];
+/// for (i, value) in src.iter_mut().enumerate() {
+///     *value = i as u16;
+/// }
+/// let mut dst = [0u16; 3];
+/// let mut desc = TransferDescriptor::new();
+/// desc.set_beat_size(BeatSize::HWord);
+/// desc.set_block_count(3);
+/// desc.set_src_addr_increment(true);
+/// desc.set_dest_addr_increment(true);
+/// desc.set_step_selection(true);
+/// desc.set_step_size(StepSize::X4);
+/// // The end addresses are 3 * 2 * 4 bytes past the source, and 3 * 2 bytes past the destination.
+/// desc.set_src_addr((src.as_ptr() as usize + 24) as *const ());
+/// desc.set_dst_addr((dst.as_mut_ptr() as usize + 6) as *const ());
+/// desc.set_valid();
+///
+/// let mut model = Model::new(&desc, 1);
+/// assert_eq!(unsafe { model.run() }, Outcome::Done);
+/// assert_eq!(dst, [0, 4, 8]);
+/// ```
+pub struct Model {
+    next: Option<*const TransferDescriptor>,
+    write_back: TransferDescriptor,
+    blocks: usize,
+    limit: usize,
+    skip_suspend: bool,
+    flags: Interrupts,
+    fetch_error: bool,
+    enabled: bool,
+    suspended: bool,
+}
+
+impl Model {
+    /// Create a model that runs the chain starting at `first`, stopping after `limit` blocks.
+    pub fn new(first: *const TransferDescriptor, limit: usize) -> Model {
+        Model {
+            next: Some(first),
+            write_back: TransferDescriptor::new(),
+            blocks: 0,
+            limit,
+            skip_suspend: false,
+            flags: Interrupts::empty(),
+            fetch_error: false,
+            enabled: true,
+            suspended: false,
+        }
+    }
+
+    /// Start a new transaction with the chain starting at `first`, like enabling the channel again with a new first
+    /// descriptor. The block count restarts from zero, and the interrupt flags are left as they are.
+    pub fn start(&mut self, first: *const TransferDescriptor) {
+        self.next = Some(first);
+        self.blocks = 0;
+        self.skip_suspend = false;
+        self.fetch_error = false;
+        self.enabled = true;
+        self.suspended = false;
+    }
+
+    /// Return the amount of blocks completed so far.
+    pub fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    /// Return the descriptor that will be fetched next, if the chain has not ended.
+    pub fn next_descriptor(&self) -> Option<*const TransferDescriptor> {
+        self.next
+    }
+
+    /// Skip the next block, by rewriting the write-back descriptor of the model the same way
+    /// `SuspendedChannel::skip_next_block()` rewrites the one of a suspended channel. Returns `false` if no block has
+    /// completed yet, or if the chain has ended.
+    pub fn skip_next_block(&mut self) -> bool {
+        if self.blocks == 0 || !self.write_back.link_past_next() {
+            return false;
+        }
+        self.next = self.write_back.get_next_desc_addr().map(|next| next as *const _);
+        true
+    }
+
+    /// Return the interrupt flags raised so far, like the CHINTFLAG register of the channel.
+    pub fn flags(&self) -> Interrupts {
+        self.flags
+    }
+
+    /// Clear the given interrupt flags, like writing ones to the CHINTFLAG register of the channel.
+    pub fn clear_flags(&mut self, flags: Interrupts) {
+        self.flags.remove(flags);
+    }
+
+    /// Poll the status of the transaction, handling and clearing the interrupt flags like `Channel::poll_status()`.
+    ///
+    /// ```
+    /// use samd_dma::{BeatSize, BlockAction, TransactionError, TransferDescriptor, WaitResult};
+    /// use samd_dma::model::{Model, Outcome};
+    ///
+    /// let src = [7u8];
+    /// let mut dst = [0u8];
+    /// let invalid = TransferDescriptor::new();
+    /// let mut valid = TransferDescriptor::new();
+    /// valid.set_beat_size(BeatSize::Byte);
+    /// valid.set_block_count(1);
+    /// valid.set_block_action(BlockAction::Int);
+    /// valid.set_src_start_addr(src.as_ptr() as *const ());
+    /// valid.set_dst_start_addr(dst.as_mut_ptr() as *const ());
+    /// valid.set_valid();
+    ///
+    /// // The first transaction fails on an invalid descriptor.
+    /// let mut model = Model::new(&invalid, 8);
+    /// assert_eq!(unsafe { model.run() }, Outcome::InvalidDescriptor);
+    /// assert_eq!(model.poll_status(), Err(TransactionError::InvalidDescriptor));
+    ///
+    /// // Polling cleared the flags, so the next transaction on the channel completes without error.
+    /// model.start(&valid);
+    /// assert_eq!(unsafe { model.run() }, Outcome::Done);
+    /// assert_eq!(model.poll_status(), Ok(WaitResult::Done));
+    /// assert_eq!(dst, [7]);
+    /// ```
+    pub fn poll_status(&mut self) -> Result<WaitResult, TransactionError> {
+        let flags = self.flags;
+        let mut status = ChannelStatus::empty();
+        status.set(ChannelStatus::FERR, self.fetch_error);
+        self.clear_flags(flags);
+        transaction_status(flags, status, self.enabled)
+    }
+
+    /// Return whether a suspend block action suspended the chain, and it has not been resumed since.
+    ///
+    /// Like `Channel::suspended()`, this does not depend on the `SUSP` flag, so the chain is still suspended after
+    /// [`poll_status()`] has reported it and cleared the flag.
+    ///
+    /// ```
+    /// use samd_dma::{BeatSize, BlockAction, Interrupts, TransferDescriptor, WaitResult};
+    /// use samd_dma::model::{Model, Outcome};
+    ///
+    /// let src: [u8; 2] = [1, 2];
+    /// let mut dst = [0u8; 2];
+    /// let mut second = TransferDescriptor::new();
+    /// second.set_beat_size(BeatSize::Byte);
+    /// second.set_block_count(1);
+    /// second.set_src_start_addr(&src[1] as *const u8 as *const ());
+    /// second.set_dst_start_addr(&mut dst[1] as *mut u8 as *const ());
+    /// second.set_valid();
+    ///
+    /// let mut first = second;
+    /// first.set_src_start_addr(&src[0] as *const u8 as *const ());
+    /// first.set_dst_start_addr(&mut dst[0] as *mut u8 as *const ());
+    /// first.set_block_action(BlockAction::Suspend);
+    /// first.set_next_desc_addr(&mut second);
+    ///
+    /// let mut model = Model::new(&first, 8);
+    /// assert_eq!(unsafe { model.run() }, Outcome::Suspended);
+    /// assert_eq!(model.poll_status(), Ok(WaitResult::Suspended));
+    /// assert_eq!(model.flags(), Interrupts::empty());
+    /// assert!(model.is_suspended());
+    ///
+    /// // Running the model again resumes the chain.
+    /// assert_eq!(unsafe { model.run() }, Outcome::Done);
+    /// assert!(!model.is_suspended());
+    /// assert_eq!(dst, [1, 2]);
+    /// ```
+    ///
+    /// [`poll_status()`]: #method.poll_status
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Skip the next suspend block action, like a skip suspend event received by the channel.
+    ///
+    /// ```
+    /// use samd_dma::{BeatSize, BlockAction, TransferDescriptor};
+    /// use samd_dma::model::{Model, Outcome};
+    ///
+    /// let src: [u8; 2] = [1, 2];
+    /// let mut dst = [0u8; 2];
+    /// let mut second = TransferDescriptor::new();
+    /// second.set_beat_size(BeatSize::Byte);
+    /// second.set_block_count(1);
+    /// second.set_src_start_addr(&src[1] as *const u8 as *const ());
+    /// second.set_dst_start_addr(&mut dst[1] as *mut u8 as *const ());
+    /// second.set_valid();
+    ///
+    /// let mut first = second;
+    /// first.set_src_start_addr(&src[0] as *const u8 as *const ());
+    /// first.set_dst_start_addr(&mut dst[0] as *mut u8 as *const ());
+    /// first.set_block_action(BlockAction::Suspend);
+    /// first.set_next_desc_addr(&mut second);
+    ///
+    /// let mut model = Model::new(&first, 8);
+    /// model.skip_suspend();
+    /// assert_eq!(unsafe { model.run() }, Outcome::Done);
+    /// assert_eq!(dst, [1, 2]);
+    /// ```
+    pub fn skip_suspend(&mut self) {
+        self.skip_suspend = true;
+    }
+
+    /// Run the chain until it ends, suspends, fetches an invalid descriptor or reaches the block limit.
+    ///
+    /// Running a suspended model again resumes the chain from the next descriptor.
+    ///
+    /// # Safety
+    ///
+    /// Every descriptor in the chain, and every address they transfer from or to, must be valid.
+    pub unsafe fn run(&mut self) -> Outcome {
+        self.suspended = false;
+        while let Some(desc) = self.next {
+            if self.blocks == self.limit {
+                return Outcome::BlockLimit;
+            }
+
+            let desc = &*desc;
+            if !desc.is_valid() {
+                self.flags.insert(Interrupts::SUSP);
+                self.fetch_error = true;
+                return Outcome::InvalidDescriptor;
+            }
+            transfer_block(desc);
+            self.blocks += 1;
+            self.write_back = *desc;
+            self.next = desc.get_next_desc_addr().map(|next| next as *const _);
+
+            let action = desc.get_block_action();
+            if let BlockAction::Int | BlockAction::Both = action {
+                self.flags.insert(Interrupts::TCMPL);
+            }
+            if let BlockAction::Suspend | BlockAction::Both = action {
+                if !self.skip_suspend {
+                    self.flags.insert(Interrupts::SUSP);
+                    self.suspended = true;
+                    return Outcome::Suspended;
+                }
+                self.skip_suspend = false;
+            }
+        }
+        self.enabled = false;
+        Outcome::Done
+    }
+}
+
+/// Move every beat of the block described by `desc`.
+unsafe fn transfer_block(desc: &TransferDescriptor) {
+    let count = desc.get_block_transfer_count() as usize;
+    let beat = 1usize << desc.get_beat_size() as usize;
+    let step = 1usize << desc.get_step_size() as usize;
+    let src_step = desc.get_step_selection();
+    let (src, src_stride) = start_and_stride(desc.get_src_addr(), desc.get_src_addr_increment(), src_step, count,
+        beat, step);
+    let (dst, dst_stride) = start_and_stride(desc.get_dst_addr(), desc.get_dest_addr_increment(), !src_step, count,
+        beat, step);
+
+    for i in 0..count {
+        ptr::copy(src.wrapping_add(i * src_stride), dst.wrapping_add(i * dst_stride) as *mut u8, beat);
+    }
+}
+
+/// Convert the address stored in a descriptor into the address of the first beat, and the distance between beats.
+fn start_and_stride(addr: Option<*const ()>, increment: bool, stepped: bool, count: usize, beat: usize,
+    step: usize) -> (*const u8, usize)
+{
+    let addr = addr.unwrap_or(ptr::null()) as *const u8;
+    if !increment {
+        return (addr, 0);
+    }
+    let stride = if stepped { beat * step } else { beat };
+    (addr.wrapping_sub(count * stride), stride)
+}
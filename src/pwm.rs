@@ -0,0 +1,180 @@
+use core::marker::PhantomData;
+use crate::target_device::{TCC0, TCC1, TCC2, TCC3, TCC4};
+use crate::{Channel, TriggerSource, TriggerAction, TransferDescriptor, BeatSize, BlockAction, StepSize, Beat};
+
+/// Offset of the first compare/capture buffer register in a TCC instance.
+const CCBUF0_OFFSET: usize = 0x70;
+
+/// TCC instance used for duty-cycle streaming.
+pub enum Tcc {
+    /// TCC0, a 24-bit counter.
+    Tcc0,
+    /// TCC1, a 24-bit counter.
+    Tcc1,
+    /// TCC2, a 16-bit counter.
+    Tcc2,
+    /// TCC3, a 16-bit counter.
+    Tcc3,
+    /// TCC4, a 16-bit counter.
+    Tcc4,
+}
+
+impl Tcc {
+    /// Return the beat size matching the width of the counter.
+    pub fn beat_size(&self) -> BeatSize {
+        match self {
+            Tcc::Tcc0 | Tcc::Tcc1 => BeatSize::Word,
+            _ => BeatSize::HWord,
+        }
+    }
+
+    /// Return the number of compare/capture channels.
+    pub fn compare_channels(&self) -> u8 {
+        match self {
+            Tcc::Tcc0 => 6,
+            Tcc::Tcc1 => 4,
+            Tcc::Tcc2 => 3,
+            Tcc::Tcc3 | Tcc::Tcc4 => 2,
+        }
+    }
+
+    fn overflow_source(&self) -> TriggerSource {
+        match self {
+            Tcc::Tcc0 => TriggerSource::Tcc0Ovf,
+            Tcc::Tcc1 => TriggerSource::Tcc1Ovf,
+            Tcc::Tcc2 => TriggerSource::Tcc2Ovf,
+            Tcc::Tcc3 => TriggerSource::Tcc3Ovf,
+            Tcc::Tcc4 => TriggerSource::Tcc4Ovf,
+        }
+    }
+
+    fn ccbuf_addr(&self, cc: u8) -> *const () {
+        let base = match self {
+            Tcc::Tcc0 => TCC0::ptr() as *const u8,
+            Tcc::Tcc1 => TCC1::ptr() as *const u8,
+            Tcc::Tcc2 => TCC2::ptr() as *const u8,
+            Tcc::Tcc3 => TCC3::ptr() as *const u8,
+            Tcc::Tcc4 => TCC4::ptr() as *const u8,
+        };
+        base.wrapping_add(CCBUF0_OFFSET + 4 * cc as usize) as *const ()
+    }
+}
+
+/// What happens when a duty-cycle stream reaches the end of its table.
+pub enum StreamEnd {
+    /// Start again from the first entry of the table.
+    Repeat,
+    /// Stop the channel, leaving the last duty cycle in place.
+    Stop,
+}
+
+/// Duty-cycle stream to TCC compare registers.
+///
+/// On each overflow of the TCC, the next entry of the duty-cycle table is written to the compare buffer of one or more
+/// consecutive compare channels, so new duty cycles take effect on the following period without glitches.
+///
+/// When a single compare channel is updated, one descriptor moves a single beat on each overflow. When several compare
+/// channels are updated, the table holds one frame per period, with one entry per compare channel. Each frame is moved
+/// as its own block, with the destination address stepped over the compare buffer registers, so one descriptor is
+/// needed per frame.
+pub struct PwmStream<T: Beat + 'static> {
+    channel: Channel,
+    descriptors: &'static mut [TransferDescriptor],
+    _table: PhantomData<&'static [T]>,
+}
+
+impl<T: Beat + 'static> PwmStream<T> {
+    /// Create a stream of `table` into the `channels` consecutive compare channels of `tcc`, starting at `first_cc`.
+    ///
+    /// `descriptors` must hold one descriptor for every frame after the first when `channels` is greater than one,
+    /// and is unused otherwise. The last descriptor raises a transfer complete interrupt when the table ends. The
+    /// channel is configured, but not enabled until [`start`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the beat size of `T` doesn't match the width of the TCC counter, the compare channels don't exist,
+    /// the table can't be split into frames of `channels` entries, there are not enough descriptors, or a block
+    /// would be longer than 65535 beats.
+    ///
+    /// [`start`]: #method.start
+    pub fn new(mut channel: Channel, tcc: Tcc, first_cc: u8, channels: u8, table: &'static [T],
+        descriptors: &'static mut [TransferDescriptor], end: StreamEnd) -> PwmStream<T>
+    {
+        assert!(T::SIZE == tcc.beat_size());
+        assert!(channels > 0 && first_cc + channels <= tcc.compare_channels());
+        let channels = channels as usize;
+        let frames = table.len() / channels;
+        assert!(frames > 0 && frames * channels == table.len());
+
+        channel.disable();
+        if channels == 1 {
+            assert!(table.len() <= u16::MAX as usize);
+            channel.set_beat_trigger(tcc.overflow_source());
+        } else {
+            assert!(descriptors.len() + 1 >= frames);
+            channel.set_source(tcc.overflow_source());
+            channel.set_trigger_action(TriggerAction::Block);
+        }
+
+        let (blocks, block_len) = if channels == 1 {
+            (1, table.len())
+        } else {
+            (frames, channels)
+        };
+        let slots = channel.chain_slots(descriptors);
+        for i in 0..blocks {
+            let desc = unsafe { &mut *slots.get(i) };
+            *desc = Default::default();
+            desc.set_beat_size(T::SIZE);
+            desc.set_block_count(block_len as u16);
+            desc.set_src_addr_increment(true);
+            desc.set_src_start_addr(table[i * block_len..].as_ptr() as *const ());
+            if channels > 1 {
+                // Compare buffer registers are a word apart, whatever the beat size.
+                desc.set_dest_addr_increment(true);
+                desc.set_step_selection(false);
+                if T::SIZE == BeatSize::HWord {
+                    desc.set_step_size(StepSize::X2);
+                }
+            }
+            desc.set_dst_start_addr(tcc.ccbuf_addr(first_cc));
+            if i + 1 < blocks {
+                desc.set_next_desc_addr(slots.get(i + 1));
+            } else {
+                desc.set_block_action(BlockAction::Int);
+                if let StreamEnd::Repeat = end {
+                    desc.set_next_desc_addr(slots.get(0));
+                }
+            }
+            desc.set_valid();
+        }
+
+        PwmStream {
+            channel,
+            descriptors,
+            _table: PhantomData,
+        }
+    }
+
+    /// Start streaming from the first entry of the table.
+    pub fn start(&mut self) {
+        self.channel.clear_interrupt_flags();
+        self.channel.enable();
+    }
+
+    /// Stop streaming. The compare channels keep their last duty cycle.
+    pub fn stop(&mut self) {
+        self.channel.disable();
+    }
+
+    /// Return `true` if a stream that stops at the end of its table has finished.
+    pub fn is_done(&self) -> bool {
+        !self.channel.is_enabled()
+    }
+
+    /// Stop the stream, returning the channel and the frame descriptors.
+    pub fn free(mut self) -> (Channel, &'static mut [TransferDescriptor]) {
+        self.channel.disable();
+        (self.channel, self.descriptors)
+    }
+}
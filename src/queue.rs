@@ -0,0 +1,247 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use crate::{Channel, Interrupts, TransactionError, TransferDescriptor, TriggerSource, WaitResult};
+
+/// Function called with the buffer and result of each completed transfer, from
+/// [`TransferQueue::handle_interrupt()`].
+///
+/// [`TransferQueue::handle_interrupt()`]: struct.TransferQueue.html#method.handle_interrupt
+pub type TransferCallback<B> = fn(B, Result<(), TransactionError>);
+
+struct Entry<B> {
+    descriptor: TransferDescriptor,
+    buffer: B,
+    result: Option<Result<(), TransactionError>>,
+}
+
+/// Storage for one transfer of a [`TransferQueue`].
+///
+/// [`TransferQueue`]: struct.TransferQueue.html
+pub struct QueueSlot<B> {
+    entry: Option<Entry<B>>,
+}
+
+impl<B> QueueSlot<B> {
+    /// Create an empty slot.
+    pub const fn empty() -> QueueSlot<B> {
+        QueueSlot {
+            entry: None,
+        }
+    }
+
+    /// Take the buffer left in the slot, such as after [`TransferQueue::free()`].
+    ///
+    /// [`TransferQueue::free()`]: struct.TransferQueue.html#method.free
+    pub fn take(&mut self) -> Option<B> {
+        self.entry.take().map(|entry| entry.buffer)
+    }
+}
+
+impl<B> Default for QueueSlot<B> {
+    fn default() -> QueueSlot<B> {
+        QueueSlot::empty()
+    }
+}
+
+/// Fixed-capacity queue of transfers run back-to-back on one channel.
+///
+/// Each transfer is a descriptor, which may link further descriptors, and a buffer that is held by the queue until
+/// the transfer completes. When a transfer ends, [`handle_interrupt`] copies the descriptor of the next transfer
+/// into the first descriptor of the channel, then enables the channel, and triggers it if it uses software triggers.
+/// The trigger source and the other channel settings are left as configured before the queue was created.
+///
+/// Completed buffers are passed to the callback set with [`set_callback`] if there is one. Otherwise, they are kept
+/// in the queue, in submission order, until taken with [`pop_completed`] or [`next_completed`]. Completed buffers
+/// that have not been taken use up queue slots.
+///
+/// [`handle_interrupt`]: #method.handle_interrupt
+/// [`set_callback`]: #method.set_callback
+/// [`pop_completed`]: #method.pop_completed
+/// [`next_completed`]: #method.next_completed
+pub struct TransferQueue<B: 'static> {
+    channel: Channel,
+    slots: &'static mut [QueueSlot<B>],
+    head: usize,
+    len: usize,
+    done: usize,
+    running: bool,
+    callback: Option<TransferCallback<B>>,
+}
+
+impl<B: 'static> TransferQueue<B> {
+    /// Create a queue of up to `slots.len()` transfers on `channel`.
+    ///
+    /// The transfer complete and transfer error interrupts of the channel are enabled.
+    pub fn new(mut channel: Channel, slots: &'static mut [QueueSlot<B>]) -> TransferQueue<B> {
+        channel.disable();
+        channel.enable_interrupts(channel.get_enabled_interrupts() | Interrupts::TCMPL | Interrupts::TERR);
+        for slot in slots.iter_mut() {
+            *slot = QueueSlot::empty();
+        }
+        TransferQueue {
+            channel,
+            slots,
+            head: 0,
+            len: 0,
+            done: 0,
+            running: false,
+            callback: None,
+        }
+    }
+
+    /// Set the function called with each completed transfer, or `None` to keep completed transfers in the queue.
+    pub fn set_callback(&mut self, callback: Option<TransferCallback<B>>) {
+        self.callback = callback;
+    }
+
+    /// Return the maximum number of transfers in the queue.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Return the number of transfers in the queue, including completed transfers that have not been taken.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the queue holds no transfer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Queue a transfer described by `descriptor`, holding `buffer` until it completes. The transfer starts
+    /// immediately if the channel is idle.
+    ///
+    /// The queue moves on to the next transfer on the transfer complete interrupt, so a single descriptor is set to
+    /// raise it when its block ends. A descriptor linking further descriptors is left as it is, and the last block of
+    /// its chain must use `BlockAction::Int` or `BlockAction::Both`, or the queue stalls.
+    ///
+    /// Returns the buffer back if the queue is full.
+    pub fn submit(&mut self, mut descriptor: TransferDescriptor, buffer: B) -> Result<(), B> {
+        if self.len == self.slots.len() {
+            return Err(buffer);
+        }
+        if descriptor.get_next_desc_addr().is_none() {
+            descriptor.enable_block_interrupt();
+        }
+        let index = self.slot_index(self.len);
+        self.slots[index].entry = Some(Entry {
+            descriptor,
+            buffer,
+            result: None,
+        });
+        self.len += 1;
+        self.start_next();
+        Ok(())
+    }
+
+    /// Handle the channel interrupt: if the running transfer has ended, start the next queued transfer, then pass
+    /// the completed transfer to the callback if there is one.
+    pub fn handle_interrupt(&mut self) {
+        if !self.running {
+            // Nothing is running, but a stray flag would keep the interrupt asserted.
+            self.channel.clear_interrupt_flags();
+            return;
+        }
+        let result = match self.channel.poll_status() {
+            Ok(WaitResult::Done) => Ok(()),
+            Ok(_) => return,
+            Err(err) => {
+                self.channel.abort();
+                Err(err)
+            },
+        };
+
+        let index = self.slot_index(self.done);
+        if let Some(entry) = self.slots[index].entry.as_mut() {
+            entry.result = Some(result);
+        }
+        self.done += 1;
+        self.running = false;
+        self.start_next();
+
+        if let Some(callback) = self.callback {
+            while let Some((buffer, result)) = self.pop_completed() {
+                callback(buffer, result);
+            }
+        }
+    }
+
+    /// Take the oldest completed transfer, with its result.
+    pub fn pop_completed(&mut self) -> Option<(B, Result<(), TransactionError>)> {
+        if self.done == 0 {
+            return None;
+        }
+        let entry = self.slots[self.head].entry.take()?;
+        self.head = (self.head + 1) % self.slots.len();
+        self.len -= 1;
+        self.done -= 1;
+        Some((entry.buffer, entry.result.unwrap_or(Ok(()))))
+    }
+
+    /// Return a future that completes with the oldest completed transfer.
+    ///
+    /// The future handles the channel itself each time it is polled, and wakes itself until a transfer has
+    /// completed, so it doesn't depend on the channel interrupt. It never completes while a callback is set.
+    pub fn next_completed(&mut self) -> NextCompleted<'_, B> {
+        NextCompleted {
+            queue: self,
+        }
+    }
+
+    /// Stop the queue, returning the channel and the slots. Buffers of transfers that have not been taken are left
+    /// in the slots, and can be taken with `QueueSlot::take`.
+    pub fn free(mut self) -> (Channel, &'static mut [QueueSlot<B>]) {
+        self.channel.disable();
+        (self.channel, self.slots)
+    }
+
+    fn slot_index(&self, offset: usize) -> usize {
+        (self.head + offset) % self.slots.len()
+    }
+
+    /// Start the oldest queued transfer, if the channel is idle.
+    fn start_next(&mut self) {
+        if self.running || self.done == self.len {
+            return;
+        }
+        let index = self.slot_index(self.done);
+        let descriptor = match self.slots[index].entry.as_ref() {
+            Some(entry) => entry.descriptor,
+            None => return,
+        };
+
+        *self.channel.get_first_descriptor() = descriptor;
+        self.channel.clear_interrupt_flags();
+        self.channel.enable();
+        if self.channel.get_source() == TriggerSource::Disable {
+            self.channel.trigger();
+        }
+        self.running = true;
+    }
+}
+
+/// A future completing with the oldest completed transfer of a queue, created by
+/// [`TransferQueue::next_completed()`].
+///
+/// [`TransferQueue::next_completed()`]: struct.TransferQueue.html#method.next_completed
+pub struct NextCompleted<'a, B: 'static> {
+    queue: &'a mut TransferQueue<B>,
+}
+
+impl<'a, B: 'static> Future for NextCompleted<'a, B> {
+    type Output = (B, Result<(), TransactionError>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.queue.handle_interrupt();
+        match this.queue.pop_completed() {
+            Some(completed) => Poll::Ready(completed),
+            None => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            },
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use core::fmt;
+use crate::{Channel, ChannelConfig, Interrupts, TransactionError, TriggerSource, WaitResult};
+
+/// Error type for the kinds of errors that can occur while using a [`SharedChannel`].
+///
+/// [`SharedChannel`]: struct.SharedChannel.html
+#[derive(Debug)]
+pub enum SharedError {
+    /// Every owner slot is in use.
+    Full,
+    /// The owner is not registered with the shared channel.
+    InvalidOwner,
+    /// The owner already has a transfer queued or running.
+    Busy,
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SharedError::Full => write!(w, "Every owner slot of the shared channel is in use."),
+            SharedError::InvalidOwner => write!(w, "The owner is not registered with the shared channel."),
+            SharedError::Busy => write!(w, "The owner already has a transfer queued or running."),
+        }
+    }
+}
+
+/// Identifies an owner of a [`SharedChannel`].
+///
+/// [`SharedChannel`]: struct.SharedChannel.html
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OwnerId(usize);
+
+impl OwnerId {
+    /// Get the index of the owner slot.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Function called when the transfer of an owner completes, from [`SharedChannel::handle_interrupt()`].
+///
+/// [`SharedChannel::handle_interrupt()`]: struct.SharedChannel.html#method.handle_interrupt
+pub type CompletionCallback = fn(OwnerId, Result<(), TransactionError>);
+
+#[derive(Clone, Copy, PartialEq)]
+enum OwnerState {
+    Idle,
+    Queued(u32),
+    Running,
+    Done(Result<(), TransactionError>),
+}
+
+/// Storage for one owner of a [`SharedChannel`].
+///
+/// [`SharedChannel`]: struct.SharedChannel.html
+pub struct SharedOwner {
+    config: Option<ChannelConfig>,
+    callback: Option<CompletionCallback>,
+    state: OwnerState,
+}
+
+impl SharedOwner {
+    /// Create an unused owner slot.
+    pub const fn new() -> SharedOwner {
+        SharedOwner {
+            config: None,
+            callback: None,
+            state: OwnerState::Idle,
+        }
+    }
+}
+
+impl Default for SharedOwner {
+    fn default() -> SharedOwner {
+        SharedOwner::new()
+    }
+}
+
+/// A channel shared between several owners, such as drivers that rarely run at the same time.
+///
+/// Each owner registers the channel configuration it needs, including the first transfer descriptor, and requests
+/// transfers. Requests are served one at a time, in the order they were made: the configuration of the owner is
+/// applied to the channel, then the channel is enabled, and triggered if it uses software triggers. When the
+/// transfer ends, the owner is notified through its callback, and its result can be taken with [`take_result`].
+///
+/// Call [`handle_interrupt`] from the DMAC interrupt vector to complete transfers and start the next one. The
+/// transfer complete and transfer error interrupts of the channel are always enabled.
+///
+/// [`take_result`]: #method.take_result
+/// [`handle_interrupt`]: #method.handle_interrupt
+pub struct SharedChannel {
+    channel: Channel,
+    owners: &'static mut [SharedOwner],
+    running: Option<usize>,
+    next_ticket: u32,
+}
+
+impl SharedChannel {
+    /// Share `channel` between up to `owners.len()` owners.
+    pub fn new(mut channel: Channel, owners: &'static mut [SharedOwner]) -> SharedChannel {
+        channel.disable();
+        for owner in owners.iter_mut() {
+            *owner = SharedOwner::new();
+        }
+        SharedChannel {
+            channel,
+            owners,
+            running: None,
+            next_ticket: 0,
+        }
+    }
+
+    /// Register an owner with its channel configuration, and an optional completion callback.
+    ///
+    /// Transfers end on the transfer complete interrupt, so a single descriptor is set to raise it when its block
+    /// ends. If the descriptor links further descriptors, the last block of the chain must use `BlockAction::Int` or
+    /// `BlockAction::Both`, or the channel is never released to the next owner.
+    pub fn register(&mut self, config: ChannelConfig, callback: Option<CompletionCallback>)
+        -> Result<OwnerId, SharedError>
+    {
+        let index = self.owners.iter().position(|owner| owner.config.is_none()).ok_or(SharedError::Full)?;
+        let owner = &mut self.owners[index];
+        owner.config = Some(config);
+        owner.callback = callback;
+        owner.state = OwnerState::Idle;
+        Ok(OwnerId(index))
+    }
+
+    /// Unregister an owner, freeing its slot. Returns `SharedError::Busy` if it has a transfer queued or running.
+    pub fn unregister(&mut self, owner: OwnerId) -> Result<(), SharedError> {
+        self.check_idle(owner)?;
+        self.owners[owner.0] = SharedOwner::new();
+        Ok(())
+    }
+
+    /// Replace the channel configuration of an owner, such as to point its descriptor at a new buffer. Returns
+    /// `SharedError::Busy` if it has a transfer queued or running.
+    pub fn set_config(&mut self, owner: OwnerId, config: ChannelConfig) -> Result<(), SharedError> {
+        self.check_idle(owner)?;
+        self.owners[owner.0].config = Some(config);
+        Ok(())
+    }
+
+    /// Queue a transfer for an owner, using its channel configuration. The transfer starts immediately if the
+    /// channel is free.
+    ///
+    /// Any result of a previous transfer that has not been taken is discarded.
+    pub fn request(&mut self, owner: OwnerId) -> Result<(), SharedError> {
+        self.check_idle(owner)?;
+        self.owners[owner.0].state = OwnerState::Queued(self.next_ticket);
+        self.next_ticket = self.next_ticket.wrapping_add(1);
+        if self.running.is_none() {
+            self.start_next();
+        }
+        Ok(())
+    }
+
+    /// Return `true` if the owner has a transfer queued or running.
+    pub fn is_busy(&self, owner: OwnerId) -> bool {
+        matches!(self.owners.get(owner.0).map(|owner| owner.state),
+            Some(OwnerState::Queued(_)) | Some(OwnerState::Running))
+    }
+
+    /// Take the result of the last transfer of an owner, if it has completed.
+    pub fn take_result(&mut self, owner: OwnerId) -> Option<Result<(), TransactionError>> {
+        let owner = self.owners.get_mut(owner.0)?;
+        match owner.state {
+            OwnerState::Done(result) => {
+                owner.state = OwnerState::Idle;
+                Some(result)
+            },
+            _ => None,
+        }
+    }
+
+    /// Handle the channel interrupt: if the running transfer has ended, notify its owner and start the next queued
+    /// transfer.
+    pub fn handle_interrupt(&mut self) {
+        let index = match self.running {
+            Some(index) => index,
+            None => {
+                // The flags of the last transfer were cleared when it ended, so any flag left now is stale, and is
+                // cleared so the interrupt doesn't fire again.
+                self.channel.clear_interrupt_flags();
+                return;
+            },
+        };
+        let result = match self.channel.poll_status() {
+            Ok(WaitResult::Done) => Ok(()),
+            Ok(_) => return,
+            Err(err) => {
+                self.channel.abort();
+                Err(err)
+            },
+        };
+
+        self.running = None;
+        let owner = &mut self.owners[index];
+        owner.state = OwnerState::Done(result);
+        if let Some(callback) = owner.callback {
+            callback(OwnerId(index), result);
+        }
+        self.start_next();
+    }
+
+    /// Stop the shared channel, returning the channel and the owner slots. Queued and running transfers are dropped.
+    pub fn free(mut self) -> (Channel, &'static mut [SharedOwner]) {
+        self.channel.disable();
+        (self.channel, self.owners)
+    }
+
+    fn check_idle(&self, owner: OwnerId) -> Result<(), SharedError> {
+        match self.owners.get(owner.0) {
+            Some(slot) if slot.config.is_some() => {
+                if self.is_busy(owner) {
+                    Err(SharedError::Busy)
+                } else {
+                    Ok(())
+                }
+            },
+            _ => Err(SharedError::InvalidOwner),
+        }
+    }
+
+    /// Start the queued transfer that was requested first, if there is one.
+    fn start_next(&mut self) {
+        let next_ticket = self.next_ticket;
+        let next = self.owners.iter().enumerate()
+            .filter_map(|(index, owner)| match owner.state {
+                // Order by age, so the ticket counter can wrap around.
+                OwnerState::Queued(ticket) => Some((next_ticket.wrapping_sub(ticket), index)),
+                _ => None,
+            })
+            .max();
+        let index = match next {
+            Some((_, index)) => index,
+            None => return,
+        };
+
+        let mut config = match self.owners[index].config {
+            Some(config) => config,
+            None => return,
+        };
+        if config.descriptor.get_next_desc_addr().is_none() {
+            config.descriptor.enable_block_interrupt();
+        }
+        self.owners[index].state = OwnerState::Running;
+        self.running = Some(index);
+
+        // The channel is disabled between transfers, so the configuration is always accepted.
+        let _ = self.channel.configure(&config);
+        self.channel.enable_interrupts(config.interrupts | Interrupts::TCMPL | Interrupts::TERR);
+        self.channel.clear_interrupt_flags();
+        self.channel.enable();
+        if config.source == TriggerSource::Disable {
+            self.channel.trigger();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use core::fmt;
+use crate::{Channels, Interrupts};
+
+/// The maximum number of channels of the DMA system.
+#[cfg(feature = "samd5x")]
+pub const MAX_CHANNELS: usize = 32;
+
+/// The maximum number of channels of the DMA system.
+#[cfg(feature = "samd21")]
+pub const MAX_CHANNELS: usize = 12;
+
+bitflags! {
+    /// A bitfield of the CHSTATUS register of a channel.
+    #[derive(Default)]
+    pub struct ChannelStatus: u8 {
+        /// A transfer is pending on the channel.
+        const PEND = 0x1;
+        /// The channel has started a transfer.
+        const BUSY = 0x2;
+        /// An invalid descriptor was fetched.
+        const FERR = 0x4;
+        /// The CRC module detected data corruption.
+        #[cfg(feature = "samd5x")]
+        const CRCERR = 0x8;
+    }
+}
+
+/// The status and interrupt flags of a channel, captured by a [`DmacSnapshot`].
+/// 
+/// [`DmacSnapshot`]: struct.DmacSnapshot.html
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct ChannelSnapshot {
+    /// The CHSTATUS register of the channel.
+    pub status: ChannelStatus,
+    /// The CHINTFLAG register of the channel.
+    pub flags: Interrupts,
+}
+
+/// The ACTIVE register of the DMA system, captured by a [`DmacSnapshot`].
+/// 
+/// [`DmacSnapshot`]: struct.DmacSnapshot.html
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct ActiveStatus {
+    /// Whether each priority level has an active request, indexed by level.
+    pub level_requests: [bool; 4],
+    /// The ID of the last channel granted access to the DMA system.
+    pub id: u8,
+    /// Whether the active channel is transferring.
+    pub busy: bool,
+    /// The block transfer count of the active channel, valid while `busy` is set.
+    pub btcnt: u16,
+}
+
+/// The state of the DMA system at one point in time, for diagnostics.
+/// 
+/// Created by `DMAController::snapshot()`. The `Debug` output only lists the channels of the storage.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DmacSnapshot {
+    /// The channels with pending interrupts, from the INTSTATUS register.
+    pub interrupt_status: Channels,
+    /// The pending channels, from the PENDCH register.
+    pub pending: Channels,
+    /// The busy channels, from the BUSYCH register.
+    pub busy: Channels,
+    /// The ACTIVE register.
+    pub active: ActiveStatus,
+    /// The state of each channel, indexed by channel ID. Only the first `channel_count` entries are captured.
+    pub channels: [ChannelSnapshot; MAX_CHANNELS],
+    /// The number of channels of the storage.
+    pub channel_count: usize,
+}
+
+impl DmacSnapshot {
+    /// Get the captured channels, indexed by channel ID.
+    pub fn channels(&self) -> &[ChannelSnapshot] {
+        &self.channels[..self.channel_count]
+    }
+}
+
+impl fmt::Debug for DmacSnapshot {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        w.debug_struct("DmacSnapshot")
+            .field("interrupt_status", &self.interrupt_status)
+            .field("pending", &self.pending)
+            .field("busy", &self.busy)
+            .field("active", &self.active)
+            .field("channels", &self.channels())
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DmacSnapshot {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "DmacSnapshot {{ intstatus: {=u32:#x}, pendch: {=u32:#x}, busych: {=u32:#x}, ",
+            self.interrupt_status.bits(), self.pending.bits(), self.busy.bits());
+        defmt::write!(f, "active: {{ lvlex: {=[?]}, id: {=u8}, abusy: {=bool}, btcnt: {=u16} }}, channels: [",
+            self.active.level_requests, self.active.id, self.active.busy, self.active.btcnt);
+        for (id, channel) in self.channels().iter().enumerate() {
+            defmt::write!(f, " {=usize}: {{ chstatus: {=u8:#x}, chintflag: {=u8:#x} }}", id, channel.status.bits(),
+                channel.flags.bits());
+        }
+        defmt::write!(f, " ] }}");
+    }
+}
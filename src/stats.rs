@@ -0,0 +1,138 @@
+use core::cell::{Cell, RefCell};
+use cortex_m::interrupt::{self, Mutex};
+use crate::{Interrupts, ChannelStatus, MAX_CHANNELS};
+
+/// Statistics counters of a channel.
+/// 
+/// The counters are updated whenever the interrupt flags of the channel are handled, either by `Channel::poll_status`
+/// or by clearing them through `DMAController::clear_interrupt_pending`. Each flag is counted by the call that clears 
+/// it, so a flag handled by both is only counted once. Counters wrap around on overflow.
+/// 
+/// A transaction is counted as completed by the first of these calls that finds the channel stopped after it was 
+/// enabled, so transactions whose last block does not raise the transfer complete flag are counted too. A 
+/// transaction stopped by `Channel::disable` or ended by an error is not counted.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct ChannelStats {
+    /// Transactions completed.
+    pub transfers: u32,
+    /// Bytes moved by the completed transactions.
+    pub bytes: u64,
+    /// Transfer errors.
+    pub transfer_errors: u32,
+    /// Descriptor fetch errors.
+    pub fetch_errors: u32,
+    /// CRC errors.
+    pub crc_errors: u32,
+    /// Suspends, not counting fetch errors.
+    pub suspends: u32,
+    /// The longest time from a software trigger to the completion of the transaction, in ticks of the clock set with
+    /// `DMAController::set_stats_clock`. Zero until a clock is set.
+    pub max_latency: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    stats: ChannelStats,
+    triggered_at: Option<u32>,
+    running: bool,
+}
+
+const EMPTY: Entry = Entry {
+    stats: ChannelStats {
+        transfers: 0,
+        bytes: 0,
+        transfer_errors: 0,
+        fetch_errors: 0,
+        crc_errors: 0,
+        suspends: 0,
+        max_latency: 0,
+    },
+    triggered_at: None,
+    running: false,
+};
+
+type Table = [Entry; MAX_CHANNELS];
+type Clock = Option<fn() -> u32>;
+
+static STATS: Mutex<RefCell<Table>> = Mutex::new(RefCell::new([EMPTY; MAX_CHANNELS]));
+static CLOCK: Mutex<Cell<Clock>> = Mutex::new(Cell::new(None));
+
+pub(crate) fn set_clock(clock: fn() -> u32) {
+    interrupt::free(|cs| CLOCK.borrow(cs).set(Some(clock)));
+}
+
+pub(crate) fn get(id: u8) -> ChannelStats {
+    interrupt::free(|cs| STATS.borrow(cs).borrow()[id as usize].stats)
+}
+
+/// Reset the counters of the channel, keeping track of a transaction that is running.
+pub(crate) fn reset(id: u8) {
+    interrupt::free(|cs| {
+        let entry = &mut STATS.borrow(cs).borrow_mut()[id as usize];
+        *entry = Entry {
+            running: entry.running,
+            ..EMPTY
+        };
+    });
+}
+
+/// Record a software trigger of the channel, starting the latency measurement.
+pub(crate) fn record_trigger(id: u8) {
+    interrupt::free(|cs| {
+        if let Some(clock) = CLOCK.borrow(cs).get() {
+            STATS.borrow(cs).borrow_mut()[id as usize].triggered_at = Some(clock());
+        }
+    });
+}
+
+/// Record the channel being enabled or disabled by software. A transaction stopped by disabling the channel is not
+/// counted as completed.
+pub(crate) fn record_running(id: u8, running: bool) {
+    interrupt::free(|cs| STATS.borrow(cs).borrow_mut()[id as usize].running = running);
+}
+
+/// Record the handled interrupt flags and status of the channel, and whether it is still enabled. `bytes` returns the 
+/// size of the transaction, and is only called if the transaction has completed.
+pub(crate) fn record<F: FnOnce() -> usize>(id: u8, flags: Interrupts, status: ChannelStatus, enabled: bool, bytes: F) {
+    interrupt::free(|cs| {
+        let mut entries = STATS.borrow(cs).borrow_mut();
+        let entry = &mut entries[id as usize];
+        let stats = &mut entry.stats;
+        #[cfg(feature = "samd5x")]
+        let crc_error = status.intersects(ChannelStatus::CRCERR);
+        #[cfg(feature = "samd21")]
+        let crc_error = false;
+        let fetch_error = status.intersects(ChannelStatus::FERR);
+
+        if flags.intersects(Interrupts::TERR) {
+            if crc_error {
+                stats.crc_errors = stats.crc_errors.wrapping_add(1);
+            } else {
+                stats.transfer_errors = stats.transfer_errors.wrapping_add(1);
+            }
+        }
+        if flags.intersects(Interrupts::SUSP) {
+            if fetch_error {
+                stats.fetch_errors = stats.fetch_errors.wrapping_add(1);
+            } else {
+                stats.suspends = stats.suspends.wrapping_add(1);
+            }
+        }
+
+        // The channel disables itself once the last block of the transaction ends, so the transaction is complete 
+        // when a running channel is found disabled.
+        let failed = flags.intersects(Interrupts::TERR) || (flags.intersects(Interrupts::SUSP) && fetch_error);
+        if failed {
+            entry.running = false;
+        } else if enabled {
+            entry.running = true;
+        } else if entry.running {
+            entry.running = false;
+            stats.transfers = stats.transfers.wrapping_add(1);
+            stats.bytes = stats.bytes.wrapping_add(bytes() as u64);
+            if let (Some(clock), Some(start)) = (CLOCK.borrow(cs).get(), entry.triggered_at.take()) {
+                stats.max_latency = stats.max_latency.max(clock().wrapping_sub(start));
+            }
+        }
+    });
+}
@@ -0,0 +1,187 @@
+use core::fmt;
+use core::mem;
+use crate::{Channel, TriggerSource, TriggerAction, TransferDescriptor, BeatSize, StepSize, Beat};
+
+/// A type whose byte order can be reversed by a [`ByteSwap`].
+///
+/// [`ByteSwap`]: struct.ByteSwap.html
+pub trait SwapBytes: Beat {
+    /// The step size covering one value of this type, in bytes.
+    const STEP: StepSize;
+}
+
+impl SwapBytes for u16 {
+    const STEP: StepSize = StepSize::X2;
+}
+
+impl SwapBytes for u32 {
+    const STEP: StepSize = StepSize::X4;
+}
+
+/// Error type for the kinds of errors that can occur while building a [`ByteSwap`] descriptor chain.
+///
+/// [`ByteSwap`]: struct.ByteSwap.html
+#[derive(Debug)]
+pub enum ByteSwapError {
+    /// The array is empty or longer than 65535 values.
+    InvalidLength,
+    /// Not enough descriptors were provided to build the descriptor chain.
+    TooFewDescriptors {
+        /// The amount of descriptors needed, including the first descriptor of the channel.
+        needed: usize,
+    },
+}
+
+impl fmt::Display for ByteSwapError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ByteSwapError::InvalidLength => write!(w, "The array must hold between 1 and 65535 values."),
+            ByteSwapError::TooFewDescriptors { needed } => write!(w, "The byte swap needs {} descriptors.", needed),
+        }
+    }
+}
+
+/// Byte order reversing transfer for arrays of `u16` or `u32`.
+///
+/// The DMA system can only step over one side of a transfer, so each byte lane is moved in two blocks: the lane is
+/// gathered from the source array into a scratch buffer by stepping over the source, then scattered from the scratch
+/// buffer into the opposite lane of the destination array by stepping over the destination. A `u16` swap needs 4
+/// linked descriptors, and a `u32` swap needs 8. The scratch buffer must hold one byte per value, and the source and
+/// destination arrays must not overlap.
+///
+/// # Example
+///
+/// The descriptor chain can be checked on a host with the software [`model`] of the descriptor engine.
+///
+/// ```
+/// use samd_dma::{ByteSwap, TransferDescriptor};
+/// use samd_dma::model::{Model, Outcome};
+///
+/// let src: [u16; 3] = [0x1122, 0x3344, 0x5566];
+/// let mut dst = [0u16; 3];
+/// let mut scratch = [0u8; 3];
+/// let mut descriptors = [TransferDescriptor::new(); 4];
+///
+/// let swap = ByteSwap::new(src.as_ptr(), dst.as_mut_ptr(), src.len(), scratch.as_mut_ptr());
+/// swap.build(&mut descriptors).unwrap();
+///
+/// let mut model = Model::new(&descriptors[0], 16);
+/// assert_eq!(unsafe { model.run() }, Outcome::Done);
+/// assert_eq!(dst, [0x2211, 0x4433, 0x6655]);
+/// ```
+///
+/// [`model`]: model/index.html
+pub struct ByteSwap<T: SwapBytes> {
+    src: *const T,
+    dst: *mut T,
+    len: usize,
+    scratch: *mut u8,
+}
+
+impl<T: SwapBytes> ByteSwap<T> {
+    /// Create a transfer that reverses the byte order of `len` values from `src` into `dst`, using `scratch` to
+    /// hold `len` bytes.
+    pub fn new(src: *const T, dst: *mut T, len: usize, scratch: *mut u8) -> ByteSwap<T> {
+        ByteSwap {
+            src,
+            dst,
+            len,
+            scratch,
+        }
+    }
+
+    /// Return the amount of descriptors needed for the transfer, including the first descriptor of the channel.
+    pub fn descriptors_needed(&self) -> usize {
+        2 * mem::size_of::<T>()
+    }
+
+    /// Build the descriptor chain into `descriptors`, starting at the first element of the slice.
+    ///
+    /// ```
+    /// use samd_dma::{ByteSwap, TransferDescriptor};
+    /// use samd_dma::model::{Model, Outcome};
+    ///
+    /// let src: [u32; 2] = [0x11223344, 0xaabbccdd];
+    /// let mut dst = [0u32; 2];
+    /// let mut scratch = [0u8; 2];
+    /// let mut descriptors = [TransferDescriptor::new(); 8];
+    ///
+    /// let swap = ByteSwap::new(src.as_ptr(), dst.as_mut_ptr(), src.len(), scratch.as_mut_ptr());
+    /// assert_eq!(swap.descriptors_needed(), 8);
+    /// swap.build(&mut descriptors).unwrap();
+    ///
+    /// let mut model = Model::new(&descriptors[0], 16);
+    /// assert_eq!(unsafe { model.run() }, Outcome::Done);
+    /// assert_eq!(model.blocks(), 8);
+    /// assert_eq!(dst, [0x44332211, 0xddccbbaa]);
+    /// ```
+    pub fn build(&self, descriptors: &mut [TransferDescriptor]) -> Result<(), ByteSwapError> {
+        let needed = self.descriptors_needed();
+        if descriptors.len() < needed {
+            return Err(ByteSwapError::TooFewDescriptors { needed });
+        }
+        let descs = descriptors.as_mut_ptr();
+        self.write_chain(|i| unsafe { descs.add(i) })
+    }
+
+    /// Build the descriptor chain into `channel`, using its first descriptor and as many of `descriptors` as needed.
+    ///
+    /// The channel is configured for a software triggered transaction, and is left disabled. Enable and trigger the
+    /// channel to start the transfer.
+    pub fn program(&self, channel: &mut Channel, descriptors: &mut [TransferDescriptor])
+        -> Result<(), ByteSwapError>
+    {
+        let needed = self.descriptors_needed();
+        if descriptors.len() + 1 < needed {
+            return Err(ByteSwapError::TooFewDescriptors { needed });
+        }
+
+        channel.disable();
+        channel.set_source(TriggerSource::Disable);
+        channel.set_trigger_action(TriggerAction::Transaction);
+
+        let slots = channel.chain_slots(descriptors);
+        self.write_chain(|i| slots.get(i))
+    }
+
+    fn write_chain<F: Fn(usize) -> *mut TransferDescriptor>(&self, slot: F) -> Result<(), ByteSwapError> {
+        if self.len == 0 || self.len > u16::MAX as usize {
+            return Err(ByteSwapError::InvalidLength);
+        }
+
+        let lanes = mem::size_of::<T>();
+        let src = self.src as *const u8;
+        let dst = self.dst as *const u8;
+        let scratch = self.scratch as *const u8;
+        for lane in 0..lanes {
+            let gather = unsafe { &mut *slot(2 * lane) };
+            *gather = Default::default();
+            gather.set_beat_size(BeatSize::Byte);
+            gather.set_block_count(self.len as u16);
+            gather.set_step_size(T::STEP);
+            gather.set_step_selection(true);
+            gather.set_src_addr_increment(true);
+            gather.set_dest_addr_increment(true);
+            gather.set_src_start_addr(src.wrapping_add(lanes - 1 - lane) as *const ());
+            gather.set_dst_start_addr(scratch as *const ());
+            gather.set_next_desc_addr(slot(2 * lane + 1));
+            gather.set_valid();
+
+            let scatter = unsafe { &mut *slot(2 * lane + 1) };
+            *scatter = Default::default();
+            scatter.set_beat_size(BeatSize::Byte);
+            scatter.set_block_count(self.len as u16);
+            scatter.set_step_size(T::STEP);
+            scatter.set_step_selection(false);
+            scatter.set_src_addr_increment(true);
+            scatter.set_dest_addr_increment(true);
+            scatter.set_src_start_addr(scratch as *const ());
+            scatter.set_dst_start_addr(dst.wrapping_add(lane) as *const ());
+            if lane + 1 < lanes {
+                scatter.set_next_desc_addr(slot(2 * lane + 2));
+            }
+            scatter.set_valid();
+        }
+        Ok(())
+    }
+}
@@ -73,6 +73,7 @@ bitflags! {
 
 bitflags! {
     /// A bitfield to represent channel interrupt flags.
+    #[derive(Default)]
     pub struct Interrupts: u8 {
         const TERR = 0x1;
         const TCMPL = 0x2;
@@ -81,6 +82,7 @@ bitflags! {
 }
 
 /// The status of a channel.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Status {
     Busy,
     Pending,
@@ -90,7 +92,9 @@ pub enum Status {
 }
 
 /// Priority level of a channel.
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum Priority {
+    #[default]
     Level0 = 0,
     Level1,
     Level2,
@@ -111,6 +115,7 @@ impl From<PRILVL_A> for Priority {
 }
 
 /// Quality of Service guarantee for the DMA system.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum QoS {
     Disable = 0,
     Low,
@@ -216,9 +221,62 @@ impl From<WRBQOS_A> for QoS {
     }
 }
 
+/// Family-neutral Quality of Service policy for the bus accesses of the DMA system.
+/// 
+/// Each policy is written with the same field value on both families:
+/// 
+/// | Policy               | Value | SAMD21 `QOSCTRL` | SAMD51 `PRICTRL0.QOSn` | `QoS`      |
+/// |----------------------|-------|------------------|------------------------|------------|
+/// | `Background`         | 0     | `DISABLE`        | `REGULAR`              | `Disable`  |
+/// | `BandwidthSensitive` | 1     | `LOW`            | `SHORTAGE`             | `Low`      |
+/// | `LatencySensitive`   | 2     | `MEDIUM`         | `SENSITIVE`            | `Medium`   |
+/// | `LatencyCritical`    | 3     | `HIGH`           | `CRITICAL`             | `Critical` |
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QosPolicy {
+    /// Accesses have no bandwidth or latency requirements.
+    Background = 0,
+    /// Accesses are short of bandwidth.
+    BandwidthSensitive,
+    /// Accesses are sensitive to latency.
+    LatencySensitive,
+    /// Accesses are critical to latency.
+    LatencyCritical,
+}
+
+impl QosPolicy {
+    pub(crate) fn from(value: u8) -> QosPolicy {
+        use self::QosPolicy::*;
+        match value {
+            0 => Background,
+            1 => BandwidthSensitive,
+            2 => LatencySensitive,
+            _ => LatencyCritical,
+        }
+    }
+}
+
+impl From<QoS> for QosPolicy {
+    fn from(qos: QoS) -> QosPolicy {
+        QosPolicy::from(qos as u8)
+    }
+}
+
+impl From<QosPolicy> for QoS {
+    fn from(policy: QosPolicy) -> QoS {
+        match policy {
+            QosPolicy::Background => QoS::Disable,
+            QosPolicy::BandwidthSensitive => QoS::Low,
+            QosPolicy::LatencySensitive => QoS::Medium,
+            QosPolicy::LatencyCritical => QoS::Critical,
+        }
+    }
+}
+
 /// Length of a burst in beats.
 #[cfg(feature = "samd5x")]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum BurstLength {
+    #[default]
     Single = 0,
     TwoBeats,
     ThreeBeats,
@@ -263,8 +321,10 @@ impl From<BURSTLEN_A> for BurstLength {
 }
 
 /// What action occurs when a trigger is received.
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum TriggerAction {
     /// Trigger starts a block transfer.
+    #[default]
     Block = 0,
     /// Trigger starts a burst transfer.
     #[cfg(feature = "samd5x")]
@@ -294,7 +354,9 @@ impl From<Variant<u8, TRIGACT_A>> for TriggerAction {
 }
 
 /// Trigger source for a channel.
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum TriggerSource {
+    #[default]
     Disable = 0,
     RtcTimestamp,
     DsuDcc0,
@@ -304,6 +366,8 @@ pub enum TriggerSource {
     Sercom1Rx,
     Sercom1Tx,
     Sercom2Rx,
+    Sercom2Tx,
+    Sercom3Rx,
     Sercom3Tx,
     Sercom4Rx,
     Sercom4Tx,
@@ -394,80 +458,82 @@ impl From<Variant<u8, TRIGSRC_A>> for TriggerSource {
             Res(6) => Sercom1Rx,
             Res(7) => Sercom1Tx,
             Res(8) => Sercom2Rx,
-            Res(9) => Sercom3Tx,
-            Res(10) => Sercom4Rx,
-            Res(11) => Sercom4Tx,
-            Res(12) => Sercom5Rx,
-            Res(13) => Sercom5Tx,
-            Res(14) => Sercom6Rx,
-            Res(15) => Sercom6Tx,
-            Res(16) => Sercom7Rx,
-            Res(17) => Sercom7Tx,
-            Res(18) => Can0Debug,
-            Res(19) => Can1Debug,
-            Res(20) => Tcc0Ovf,
-            Res(21) => Tcc0Mc0,
-            Res(22) => Tcc0Mc1,
-            Res(23) => Tcc0Mc2,
-            Res(24) => Tcc0Mc3,
-            Res(25) => Tcc0Mc4,
-            Res(26) => Tcc0Mc5,
-            Res(27) => Tcc1Ovf,
-            Res(28) => Tcc1Mc0,
-            Res(29) => Tcc1Mc1,
-            Res(30) => Tcc1Mc2,
-            Res(31) => Tcc1Mc3,
-            Res(32) => Tcc2Ovf,
-            Res(33) => Tcc2Mc0,
-            Res(34) => Tcc2Mc1,
-            Res(35) => Tcc2Mc2,
-            Res(36) => Tcc3Ovf,
-            Res(37) => Tcc3Mc0,
-            Res(38) => Tcc3Mc1,
-            Res(39) => Tcc4Ovf,
-            Res(40) => Tcc4Mc0,
-            Res(41) => Tcc4Mc1,
-            Res(42) => Tc0Ovf,
-            Res(43) => Tc0Mc0,
-            Res(44) => Tc0Mc1,
-            Res(45) => Tc1Ovf,
-            Res(46) => Tc1Mc0,
-            Res(47) => Tc1Mc1,
-            Res(48) => Tc2Ovf,
-            Res(49) => Tc2Mc0,
-            Res(50) => Tc2Mc1,
-            Res(51) => Tc3Ovf,
-            Res(52) => Tc3Mc0,
-            Res(53) => Tc3Mc1,
-            Res(54) => Tc4Ovf,
-            Res(55) => Tc4Mc0,
-            Res(56) => Tc4Mc1,
-            Res(57) => Tc5Ovf,
-            Res(58) => Tc5Mc0,
-            Res(59) => Tc5Mc1,
-            Res(60) => Tc6Ovf,
-            Res(61) => Tc6Mc0,
-            Res(62) => Tc6Mc1,
-            Res(63) => Tc7Ovf,
-            Res(64) => Tc7Mc0,
-            Res(65) => Tc7Mc1,
-            Res(66) => Adc0ResRdy,
-            Res(67) => Adc0Seq,
-            Res(68) => Adc1ResRdy,
-            Res(69) => Adc1Seq,
-            Res(70) => Dac0Empty,
-            Res(71) => Dac1Empty,
-            Res(72) => Dac0ResRdy,
-            Res(73) => Dac1ResRdy,
-            Res(74) => I2sRx0,
-            Res(75) => I2sRx1,
-            Res(76) => I2sTx0,
-            Res(77) => I2sTx1,
-            Res(78) => PccRx,
-            Res(79) => AesWr,
-            Res(80) => AesRd,
-            Res(81) => QspiRx,
-            Res(82) => QspiTx,
+            Res(9) => Sercom2Tx,
+            Res(10) => Sercom3Rx,
+            Res(11) => Sercom3Tx,
+            Res(12) => Sercom4Rx,
+            Res(13) => Sercom4Tx,
+            Res(14) => Sercom5Rx,
+            Res(15) => Sercom5Tx,
+            Res(16) => Sercom6Rx,
+            Res(17) => Sercom6Tx,
+            Res(18) => Sercom7Rx,
+            Res(19) => Sercom7Tx,
+            Res(20) => Can0Debug,
+            Res(21) => Can1Debug,
+            Res(22) => Tcc0Ovf,
+            Res(23) => Tcc0Mc0,
+            Res(24) => Tcc0Mc1,
+            Res(25) => Tcc0Mc2,
+            Res(26) => Tcc0Mc3,
+            Res(27) => Tcc0Mc4,
+            Res(28) => Tcc0Mc5,
+            Res(29) => Tcc1Ovf,
+            Res(30) => Tcc1Mc0,
+            Res(31) => Tcc1Mc1,
+            Res(32) => Tcc1Mc2,
+            Res(33) => Tcc1Mc3,
+            Res(34) => Tcc2Ovf,
+            Res(35) => Tcc2Mc0,
+            Res(36) => Tcc2Mc1,
+            Res(37) => Tcc2Mc2,
+            Res(38) => Tcc3Ovf,
+            Res(39) => Tcc3Mc0,
+            Res(40) => Tcc3Mc1,
+            Res(41) => Tcc4Ovf,
+            Res(42) => Tcc4Mc0,
+            Res(43) => Tcc4Mc1,
+            Res(44) => Tc0Ovf,
+            Res(45) => Tc0Mc0,
+            Res(46) => Tc0Mc1,
+            Res(47) => Tc1Ovf,
+            Res(48) => Tc1Mc0,
+            Res(49) => Tc1Mc1,
+            Res(50) => Tc2Ovf,
+            Res(51) => Tc2Mc0,
+            Res(52) => Tc2Mc1,
+            Res(53) => Tc3Ovf,
+            Res(54) => Tc3Mc0,
+            Res(55) => Tc3Mc1,
+            Res(56) => Tc4Ovf,
+            Res(57) => Tc4Mc0,
+            Res(58) => Tc4Mc1,
+            Res(59) => Tc5Ovf,
+            Res(60) => Tc5Mc0,
+            Res(61) => Tc5Mc1,
+            Res(62) => Tc6Ovf,
+            Res(63) => Tc6Mc0,
+            Res(64) => Tc6Mc1,
+            Res(65) => Tc7Ovf,
+            Res(66) => Tc7Mc0,
+            Res(67) => Tc7Mc1,
+            Res(68) => Adc0ResRdy,
+            Res(69) => Adc0Seq,
+            Res(70) => Adc1ResRdy,
+            Res(71) => Adc1Seq,
+            Res(72) => Dac0Empty,
+            Res(73) => Dac1Empty,
+            Res(74) => Dac0ResRdy,
+            Res(75) => Dac1ResRdy,
+            Res(76) => I2sRx0,
+            Res(77) => I2sRx1,
+            Res(78) => I2sTx0,
+            Res(79) => I2sTx1,
+            Res(80) => PccRx,
+            Res(81) => AesWr,
+            Res(82) => AesRd,
+            Res(83) => QspiRx,
+            Res(84) => QspiTx,
             Res(_) => Disable,
         }
     }
@@ -475,7 +541,9 @@ impl From<Variant<u8, TRIGSRC_A>> for TriggerSource {
 
 /// Number of beats before destination writes occur.
 #[cfg(feature = "samd5x")]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum FifoThreshold {
+    #[default]
     OneBeat = 0,
     TwoBeats,
     FourBeats,
@@ -496,7 +564,7 @@ impl From<THRESHOLD_A> for FifoThreshold {
 }
 
 /// When EVSYS events should be output.
-#[derive(SmartDefault)]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum EventOutput {
     #[default]
     Disable = 0,
@@ -516,8 +584,73 @@ impl EventOutput {
     }
 }
 
+/// Action taken by a channel when it receives an input event.
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
+pub enum EventAction {
+    /// No action.
+    #[default]
+    NoAct = 0,
+    /// The event triggers a transfer.
+    Trigger,
+    /// The event is required for triggers to start a transfer.
+    ConditionalTrigger,
+    /// The event is required for a block transfer to start.
+    ConditionalBlock,
+    /// The event suspends the channel.
+    Suspend,
+    /// The event resumes the channel.
+    Resume,
+    /// The event skips the next block suspend action.
+    SkipSuspend,
+    /// The event increases the priority of the channel.
+    #[cfg(feature = "samd5x")]
+    IncreasePriority,
+}
+
+impl EventAction {
+    pub(crate) fn from(value: u8) -> EventAction {
+        use self::EventAction::*;
+        match value {
+            1 => Trigger,
+            2 => ConditionalTrigger,
+            3 => ConditionalBlock,
+            4 => Suspend,
+            5 => Resume,
+            6 => SkipSuspend,
+            #[cfg(feature = "samd5x")]
+            7 => IncreasePriority,
+            _ => NoAct,
+        }
+    }
+}
+
+/// What the channel event output signals.
+#[cfg(feature = "samd5x")]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
+pub enum EventOutputMode {
+    /// Events are output as selected by the event output of each descriptor.
+    #[default]
+    Block = 0,
+    /// Events are output for each ongoing trigger action.
+    TriggerAction,
+}
+
+/// Event input and output settings of a channel.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct EventConfig {
+    /// The action taken when an input event is received.
+    pub action: EventAction,
+    /// Whether the channel reacts to input events.
+    pub input: bool,
+    /// Whether the channel outputs events.
+    pub output: bool,
+    /// What the channel event output signals.
+    #[cfg(feature = "samd5x")]
+    pub output_mode: EventOutputMode,
+}
+
 /// Define what happens when a block transfer completes.
-#[derive(SmartDefault)]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum BlockAction {
     /// Channel will be disabled if this is the last block transfer.
     #[default]
@@ -544,12 +677,39 @@ impl BlockAction {
 }
 
 /// Size of a DMA beat transfer memory access.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum BeatSize {
     Byte = 0,
     HWord,
     Word
 }
 
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// A type that is moved by a single DMA beat transfer.
+pub trait Beat: sealed::Sealed + Copy + Default {
+    /// The beat size used to transfer a value of this type.
+    const SIZE: BeatSize;
+}
+
+impl Beat for u8 {
+    const SIZE: BeatSize = BeatSize::Byte;
+}
+
+impl Beat for u16 {
+    const SIZE: BeatSize = BeatSize::HWord;
+}
+
+impl Beat for u32 {
+    const SIZE: BeatSize = BeatSize::Word;
+}
+
 impl BeatSize {
     pub(crate) fn from(value: u16) -> BeatSize {
         use self::BeatSize::*;
@@ -563,7 +723,7 @@ impl BeatSize {
 }
 
 /// Size of the address advancement step.
-#[derive(SmartDefault)]
+#[derive(SmartDefault, Clone, Copy, PartialEq, Debug)]
 pub enum StepSize {
     #[default]
     X1 = 0,
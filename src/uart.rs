@@ -0,0 +1,162 @@
+use core::fmt;
+use core::ptr;
+use crate::{Channel, TriggerSource, TransferDescriptor, BeatSize, BlockAction, Interrupts};
+
+/// Error type for the kinds of errors that can occur while reading from a [`DmaUartRx`].
+///
+/// [`DmaUartRx`]: struct.DmaUartRx.html
+#[derive(Debug)]
+pub enum UartRxError {
+    /// The DMA engine lapped the reader, overwriting bytes that had not been read yet. The reader has been moved to
+    /// the newest received byte, and `lost` bytes were discarded.
+    Overrun {
+        /// The amount of bytes that were received but never read.
+        lost: usize,
+    },
+}
+
+impl fmt::Display for UartRxError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UartRxError::Overrun { lost } => write!(w, "The receive buffer overran, {} bytes were lost.", lost),
+        }
+    }
+}
+
+/// DMA-backed SERCOM USART receiver.
+///
+/// The receiver continuously captures the USART data register into a ring buffer using a circular descriptor, so
+/// bytes are never dropped by the peripheral while the CPU is busy. The amount of bytes received is derived from the
+/// write-back descriptor of the channel, which allows [`read`] to return bytes as soon as they arrive, without
+/// waiting for the buffer to fill (such as when the line goes idle).
+///
+/// The channel raises a transfer complete interrupt every time the ring buffer wraps. To reliably detect the DMA
+/// engine lapping the reader, call [`handle_interrupt`] from the DMAC interrupt vector. If the interrupt is not
+/// serviced, a wrap is still detected by [`read`], but only a single lap can be accounted for.
+///
+/// [`read`]: #method.read
+/// [`handle_interrupt`]: #method.handle_interrupt
+pub struct DmaUartRx {
+    channel: Channel,
+    buffer: &'static mut [u8],
+    laps: u64,
+    read_pos: u64,
+}
+
+impl DmaUartRx {
+    /// Create a receiver that captures bytes from the USART data register at `data_reg` into `buffer`.
+    ///
+    /// `source` must be the RX trigger of the SERCOM that owns `data_reg`, e.g. `TriggerSource::Sercom0Rx`. The
+    /// channel is configured, but not enabled until [`start`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is empty or longer than 65535 bytes, as a single block transfer is used for the whole ring.
+    ///
+    /// [`start`]: #method.start
+    pub fn new(mut channel: Channel, data_reg: *const (), source: TriggerSource,
+        buffer: &'static mut [u8]) -> DmaUartRx
+    {
+        assert!(!buffer.is_empty() && buffer.len() <= u16::MAX as usize);
+
+        channel.disable();
+        channel.set_beat_trigger(source);
+        channel.enable_interrupts(Interrupts::TCMPL);
+
+        let desc = channel.get_first_descriptor();
+        let desc_addr = desc as *mut _;
+        *desc = Default::default();
+        desc.set_beat_size(BeatSize::Byte);
+        desc.set_block_count(buffer.len() as u16);
+        desc.set_dest_addr_increment(true);
+        desc.set_block_action(BlockAction::Int);
+        desc.set_src_addr(data_reg);
+        desc.set_dst_start_addr(buffer.as_ptr() as *const ());
+        desc.set_next_desc_addr(desc_addr);
+        desc.set_valid();
+
+        DmaUartRx {
+            channel,
+            buffer,
+            laps: 0,
+            read_pos: 0,
+        }
+    }
+
+    /// Start capturing bytes. Any bytes left in the ring buffer are discarded.
+    pub fn start(&mut self) {
+        self.laps = 0;
+        self.read_pos = 0;
+        unsafe { ptr::write_volatile(self.channel.get_writeback_descriptor(), TransferDescriptor::new()) };
+        self.channel.clear_interrupt_flags();
+        self.channel.enable();
+    }
+
+    /// Stop capturing bytes. Bytes already received can still be read.
+    pub fn stop(&mut self) {
+        self.channel.disable();
+    }
+
+    /// Account for the DMA engine wrapping around the ring buffer.
+    ///
+    /// This should be called from the DMAC interrupt vector when this channel has a pending interrupt.
+    pub fn handle_interrupt(&mut self) {
+        if self.channel.get_interrupt_flags().contains(Interrupts::TCMPL) {
+            self.channel.clear_interrupt_flags();
+            self.laps += 1;
+        }
+    }
+
+    /// Return the amount of bytes that have been received but not read yet.
+    ///
+    /// If the DMA engine has lapped the reader, `UartRxError::Overrun` is returned and the unread bytes are discarded.
+    pub fn available(&mut self) -> Result<usize, UartRxError> {
+        self.handle_interrupt();
+        let written = self.written();
+
+        // The write-back descriptor can report a new lap before its interrupt has been handled.
+        if written < self.read_pos {
+            return Ok(0);
+        }
+
+        let pending = (written - self.read_pos) as usize;
+        if pending > self.buffer.len() {
+            self.read_pos = written;
+            return Err(UartRxError::Overrun { lost: pending });
+        }
+        Ok(pending)
+    }
+
+    /// Copy received bytes into `buf`, returning how many bytes were copied.
+    ///
+    /// This returns immediately with whatever bytes have been received so far, which may be none. If the DMA engine
+    /// has lapped the reader, `UartRxError::Overrun` is returned and the unread bytes are discarded.
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, UartRxError> {
+        let len = self.buffer.len();
+        let count = self.available()?.min(buf.len());
+        let start = (self.read_pos % len as u64) as usize;
+        let first = count.min(len - start);
+        buf[..first].copy_from_slice(&self.buffer[start..start + first]);
+        buf[first..count].copy_from_slice(&self.buffer[..count - first]);
+
+        // Check the copied bytes weren't overwritten while they were being copied.
+        self.available()?;
+        self.read_pos += count as u64;
+        Ok(count)
+    }
+
+    /// Stop the receiver, returning the channel and the ring buffer.
+    pub fn free(mut self) -> (Channel, &'static mut [u8]) {
+        self.channel.disable();
+        (self.channel, self.buffer)
+    }
+
+    /// The total amount of bytes written by the DMA engine since the receiver was started.
+    fn written(&self) -> u64 {
+        let len = self.buffer.len() as u64;
+        let remaining = self.channel.read_writeback_descriptor().get_block_transfer_count() as u64;
+        // A write-back count of zero means no block has been fetched yet.
+        let in_lap = if remaining == 0 { 0 } else { len - remaining };
+        self.laps * len + in_lap
+    }
+}